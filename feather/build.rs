@@ -1,5 +1,7 @@
 fn main() {
-    if cfg!(target_os = "macos") && pkg_config::probe_library("mpv").is_err() {
+    // Builds without the libmpv feature run the mpv executable instead of linking it
+    let links_mpv = std::env::var_os("CARGO_FEATURE_LIBMPV").is_some();
+    if links_mpv && cfg!(target_os = "macos") && pkg_config::probe_library("mpv").is_err() {
         println!("cargo:warning=Could not find mpv via pkg-config. Make sure it is installed");
     }
 }
@@ -0,0 +1,247 @@
+// This file saves Feather's state into one file and merges such files back in
+use crate::database::{HistoryDB, HistoryEntry, HistoryError};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+
+/// Format version written by `export`; archives from newer versions are refused.
+pub const VERSION: u32 = 1;
+
+/// Set while an archive is being written, so two writers never race.
+static WRITING: AtomicBool = AtomicBool::new(false);
+
+/// Everything an archive holds. Downloads are left out, their files stay on the old disk.
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    version: u32,               // Format version, see `VERSION`
+    history: Vec<HistoryEntry>, // Played songs, most recent first
+}
+
+/// Represents possible errors when exporting or importing an archive.
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error), // Reading or writing the archive file
+    #[error("Not a valid Feather archive: {0}")]
+    Format(#[from] serde_json::Error), // Truncated, corrupt or foreign file
+    #[error(
+        "Archive version {0} is newer than this Feather can read ({})",
+        VERSION
+    )]
+    UnsupportedVersion(u32), // Written by a newer Feather
+    #[error("History database error: {0}")]
+    History(#[from] HistoryError), // Reading or merging the history
+    #[error("Another export is in progress")]
+    Busy, // A second writer gave way
+}
+
+/// The state encoded as an archive, ready to be written.
+pub struct Snapshot {
+    entries: usize, // History entries it holds
+    bytes: Vec<u8>, // The archive file's contents
+}
+
+impl Snapshot {
+    /// Encodes the current history.
+    pub fn take(history: &HistoryDB) -> Result<Self, ArchiveError> {
+        let archive = Archive {
+            version: VERSION,
+            history: history.get_history()?,
+        };
+        Ok(Self {
+            entries: archive.history.len(),
+            bytes: serde_json::to_vec_pretty(&archive)?,
+        })
+    }
+
+    /// Returns how many history entries the snapshot holds.
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns a hash of the contents, equal for snapshots of unchanged data.
+    pub fn hash(&self) -> u64 {
+        hash(&self.bytes)
+    }
+
+    /// Writes the snapshot to `path`. It is written next to it and renamed over, so a
+    /// failed write leaves an earlier archive intact. Fails with `ArchiveError::Busy`
+    /// while another snapshot is being written.
+    pub fn write(&self, path: &Path) -> Result<(), ArchiveError> {
+        if WRITING.swap(true, Ordering::SeqCst) {
+            return Err(ArchiveError::Busy);
+        }
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let written = fs::write(&temp, &self.bytes).and_then(|_| fs::rename(&temp, path));
+        WRITING.store(false, Ordering::SeqCst);
+        Ok(written?)
+    }
+}
+
+/// Hashes archive contents the way `Snapshot::hash` does, e.g. for a file written before.
+/// Only meant for comparisons within one build of Feather.
+pub fn hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Writes the history to `path` as a versioned JSON archive and returns how many
+/// entries it holds.
+pub fn export(history: &HistoryDB, path: &Path) -> Result<usize, ArchiveError> {
+    let snapshot = Snapshot::take(history)?;
+    snapshot.write(path)?;
+    Ok(snapshot.entries())
+}
+
+/// Merges the archive at `path` into the history and returns how many entries were
+/// taken over. The whole archive is read and checked first, so a partial or corrupt
+/// file changes nothing.
+pub fn import(history: &HistoryDB, path: &Path) -> Result<usize, ArchiveError> {
+    let archive: Archive = serde_json::from_slice(&fs::read(path)?)?;
+    if archive.version > VERSION {
+        return Err(ArchiveError::UnsupportedVersion(archive.version));
+    }
+    Ok(history.merge(archive.history)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::HISTORY_LIMIT;
+    use serde_json::json;
+
+    fn ids(history: &HistoryDB) -> Vec<String> {
+        history
+            .get_history()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.song_id)
+            .collect()
+    }
+
+    // Writes an archive holding songs given as (ID, play time), named after their ID
+    fn write_archive(path: &Path, version: u32, songs: &[(&str, u64)]) {
+        let history: Vec<_> = songs
+            .iter()
+            .map(|(id, time_stamp)| {
+                json!({
+                    "song_name": format!("Song {}", id),
+                    "song_id": id,
+                    "artist_name": ["Artist"],
+                    "time_stamp": time_stamp,
+                })
+            })
+            .collect();
+        let archive = json!({ "version": version, "history": history });
+        fs::write(path, archive.to_string()).unwrap();
+    }
+
+    fn history_with(dir: &Path, song_id: &str) -> HistoryDB {
+        let history = HistoryDB::new_at(dir).unwrap();
+        let entry = HistoryEntry::new("Song".to_string(), song_id.to_string(), Vec::new()).unwrap();
+        history.add_entry(&entry).unwrap();
+        history
+    }
+
+    #[test]
+    fn exported_history_imports_into_a_fresh_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = history_with(&dir.path().join("source"), "a");
+        let entry = HistoryEntry::new("Other".to_string(), "b".to_string(), Vec::new()).unwrap();
+        source.add_entry(&entry).unwrap();
+        let path = dir.path().join("archive.json");
+        assert_eq!(export(&source, &path).unwrap(), 2);
+
+        let target = HistoryDB::new_at(&dir.path().join("target")).unwrap();
+        assert_eq!(import(&target, &path).unwrap(), 2);
+        // Both were played within the same second, so their order is not compared
+        let mut exported = ids(&source);
+        let mut imported = ids(&target);
+        exported.sort();
+        imported.sort();
+        assert_eq!(imported, exported);
+    }
+
+    #[test]
+    fn import_keeps_the_newer_play_of_a_song() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history_with(&dir.path().join("db"), "a");
+
+        // "a" was played just now, later than in the archive
+        let older = dir.path().join("older.json");
+        write_archive(&older, VERSION, &[("a", 1), ("b", 2)]);
+        assert_eq!(import(&history, &older).unwrap(), 1);
+        let entries = history.get_history().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].song_name, "Song"); // The local play of "a" stays on top
+
+        let newer = dir.path().join("newer.json");
+        write_archive(&newer, VERSION, &[("a", 4_102_444_800)]); // Year 2100
+        assert_eq!(import(&history, &newer).unwrap(), 1);
+        let entries = history.get_history().unwrap();
+        assert_eq!(entries[0].song_id, "a");
+        assert_eq!(entries[0].song_name, "Song a");
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn import_respects_the_history_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = HistoryDB::new_at(&dir.path().join("db")).unwrap();
+        let total = HISTORY_LIMIT + 10;
+        let songs: Vec<(String, u64)> = (0..total)
+            .map(|i| (format!("{:03}", i), i as u64 + 1))
+            .collect();
+        let songs: Vec<(&str, u64)> = songs
+            .iter()
+            .map(|(id, time)| (id.as_str(), *time))
+            .collect();
+        let path = dir.path().join("archive.json");
+        write_archive(&path, VERSION, &songs);
+
+        assert_eq!(import(&history, &path).unwrap(), HISTORY_LIMIT);
+        let kept = ids(&history);
+        assert_eq!(kept.len(), HISTORY_LIMIT);
+        // The ten oldest plays are the ones left out
+        assert_eq!(kept.first().map(String::as_str), Some("059"));
+        assert_eq!(kept.last().map(String::as_str), Some("010"));
+    }
+
+    #[test]
+    fn truncated_archive_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history_with(&dir.path().join("db"), "a");
+        let path = dir.path().join("truncated.json");
+        fs::write(
+            &path,
+            r#"{"version":1,"history":[{"song_name":"Song b","song_id":"b","#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            import(&history, &path),
+            Err(ArchiveError::Format(_))
+        ));
+        assert_eq!(ids(&history), ["a"]);
+    }
+
+    #[test]
+    fn newer_archive_version_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = history_with(&dir.path().join("db"), "a");
+        let path = dir.path().join("future.json");
+        write_archive(&path, VERSION + 1, &[("b", 1)]);
+
+        assert!(matches!(
+            import(&history, &path),
+            Err(ArchiveError::UnsupportedVersion(2))
+        ));
+        assert_eq!(ids(&history), ["a"]);
+    }
+}
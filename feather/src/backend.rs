@@ -0,0 +1,624 @@
+// This file glues the YouTube client, the player and the database into what the frontends play with
+use crate::{
+    ArtistName, SongId, SongName,
+    cookies::{CookieJar, CookieSource},
+    database::{Downloads, HistoryDB, HistoryEntry, Lyrics, MetadataCache, SongMetadata},
+    paths::Paths,
+    player::{MpvError, Player, WeakPlayer},
+    proxy::Proxy,
+    yt::YoutubeClient,
+};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use thiserror::Error;
+
+/// The `Backend` struct manages the YouTube client, music player, and history database.
+/// It also tracks the currently playing song.
+///
+/// The current song sits behind a std mutex that is only locked for short synchronous
+/// sections, never across an await; a poisoned lock is reported as
+/// `BackendError::MutexPoisoned` or skipped rather than unwrapped.
+pub struct Backend {
+    pub yt: YoutubeClient,         // YouTube client for fetching song URLs
+    pub player: Player,            // Music player instance
+    pub history: Arc<HistoryDB>,   // Shared history database
+    pub song: Mutex<Option<Song>>, // Mutex-protected optional current song
+    pub downloads: Downloads,      // Songs saved for offline playback
+    metadata: MetadataCache,       // Durations, channels and thumbnails fetched before
+    song_changes: watch::Sender<Option<Song>>, // Announces each song handed to the player
+    events: broadcast::Sender<PlayerEvent>, // Playback progress for the player view
+    end_watch: Once,               // Starts following mpv for songs that end
+    shutdown: watch::Sender<bool>, // Set once the app is quitting
+    incognito: AtomicBool,         // Whether plays are kept out of history
+    plays: AtomicU64,              // Generation of the latest play request
+    cookies: Option<CookieSource>, // Where cookies are (re)loaded from
+    cookies_file: PathBuf,         // Copy of the loaded cookies handed to mpv
+}
+
+/// Represents a song with its name, ID, and artist(s).
+/// Songs from local music folders use their absolute file path as the ID.
+#[derive(Clone)]
+pub struct Song {
+    pub song_name: SongName,          // Name of the song
+    pub song_id: SongId,              // Unique identifier for the song
+    pub artist_name: Vec<ArtistName>, // List of artists performing the song
+    pub duration_secs: Option<u64>,   // Length, when it was fetched along with the song
+    pub thumbnail: Option<String>,    // URL of the cover art, likewise
+}
+
+/// What happened to playback, as announced by `Backend::events`.
+#[derive(Clone)]
+pub enum PlayerEvent {
+    SongStarted(Song), // The song was handed to mpv and is loading
+    SongEnded,         // mpv ran out of song, which only happens with looping turned off
+    Error(String),     // The latest requested song could not be played
+}
+
+/// Where a song's audio comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    YouTube,        // Streamed, or played from a download
+    Local(PathBuf), // A file in one of the local music folders
+}
+
+/// Where `Backend::play_source` gets the audio it hands to mpv.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaybackSource {
+    YoutubeId(String), // Played from a download if there is one, streamed otherwise
+    Url(String),       // Any stream mpv can open, played as is
+    File(PathBuf),     // A file on disk
+}
+
+/// Implements conversion from `Song` to `HistoryEntry`, ensuring valid history records.
+impl From<Song> for HistoryEntry {
+    fn from(value: Song) -> Self {
+        HistoryEntry::new(value.song_name, value.song_id, value.artist_name)
+            .expect("Cannot Form History Entry")
+    }
+}
+
+impl Song {
+    /// Creates a new `Song` instance.
+    pub fn new(song_name: SongName, song_id: SongId, artist_name: Vec<ArtistName>) -> Self {
+        Self {
+            song_name,
+            song_id,
+            artist_name,
+            duration_secs: None,
+            thumbnail: None,
+        }
+    }
+
+    /// Adds the length and cover art, for sources that return them with the song.
+    pub fn with_details(mut self, duration_secs: Option<u64>, thumbnail: Option<String>) -> Self {
+        self.duration_secs = duration_secs;
+        self.thumbnail = thumbnail;
+        self
+    }
+
+    /// Returns where the song's audio comes from, derived from its ID.
+    pub fn source(&self) -> Source {
+        let path = Path::new(&self.song_id);
+        if path.is_absolute() {
+            Source::Local(path.to_path_buf())
+        } else {
+            Source::YouTube
+        }
+    }
+
+    /// Returns what `play_music` plays for the song, derived from its ID.
+    pub fn playback_source(&self) -> PlaybackSource {
+        match self.source() {
+            Source::YouTube => PlaybackSource::YoutubeId(self.song_id.clone()),
+            Source::Local(path) => PlaybackSource::File(path),
+        }
+    }
+
+    /// Returns the YouTube watch URL of the song, or a `file://` URL for local files.
+    pub fn url(&self) -> String {
+        match self.source() {
+            Source::YouTube => format!("https://youtube.com/watch?v={}", self.song_id),
+            Source::Local(path) => format!("file://{}", path.display()),
+        }
+    }
+}
+
+/// Defines possible errors that can occur in the `Backend`.
+#[derive(Error, Debug)]
+pub enum BackendError {
+    #[error("Player error: {0}")]
+    Mpv(#[from] MpvError), // Error related to the music player
+
+    #[error("Failed to fetch YouTube URL: {0}")]
+    YoutubeFetch(String), // Error when fetching a song URL from YouTube
+
+    #[error("Mutex poisoned: {0}")]
+    MutexPoisoned(String), // Error when accessing a poisoned mutex
+
+    #[error("History database error: {0}")]
+    HistoryError(String), // Error related to history database operations
+
+    #[error("Playback error: {0}")]
+    PlaybackError(String), // Error related to playback issues
+
+    #[error("Lyrics lookup failed: {0}")]
+    LyricsError(String), // Error when fetching lyrics
+
+    #[error("Cookies error: {0}")]
+    CookieError(String), // Error when loading or applying cookies
+
+    #[error("A newer song was requested")]
+    Superseded, // Another play request started before this one finished
+}
+
+impl Backend {
+    /// Creates a new `Backend` instance.
+    ///
+    /// # Arguments
+    /// * `history` - Shared reference to the history database.
+    /// * `paths` - Locations of Feather's on-disk state.
+    /// * `cookies` - Optional source of YouTube cookies, applied by `reload_cookies`.
+    ///
+    /// # Returns
+    /// * `Result<Self, BackendError>` - Returns `Backend` on success or an error on failure.
+    pub fn new(
+        history: Arc<HistoryDB>,
+        paths: &Paths,
+        cookies: Option<CookieSource>,
+    ) -> Result<Self, BackendError> {
+        // mpv only speaks HTTP proxies, with others its streams connect directly
+        let proxy = Proxy::from_env().ok().flatten().filter(Proxy::is_http);
+        let player =
+            Player::new(None, proxy.as_ref().map(Proxy::url)).map_err(BackendError::Mpv)?;
+
+        // Apply the default volume before anything plays
+        if let Some(volume) = env::var("FEATHER_VOLUME")
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+        {
+            player.set_volume(volume).map_err(BackendError::Mpv)?;
+        }
+        Self::with_player(player, history, paths, cookies)
+    }
+
+    /// Creates a `Backend` around a player that is already set up, such as one
+    /// wrapping `testing::FakeMpv`.
+    pub fn with_player(
+        player: Player,
+        history: Arc<HistoryDB>,
+        paths: &Paths,
+        cookies: Option<CookieSource>,
+    ) -> Result<Self, BackendError> {
+        let downloads = history
+            .downloads()
+            .map_err(|e| BackendError::HistoryError(e.to_string()))?;
+        let metadata = history
+            .metadata_cache()
+            .map_err(|e| BackendError::HistoryError(e.to_string()))?;
+        let _ = metadata.prune(); // A failed cleanup only leaves stale entries behind
+        let yt = YoutubeClient::new_at(&paths.data_dir)
+            .map_err(|e| BackendError::YoutubeFetch(e.to_string()))?;
+        yt.cache_metadata(metadata.clone());
+
+        Ok(Self {
+            yt,
+            downloads,
+            metadata,
+            player,
+            history,
+            song: Mutex::new(None),
+            song_changes: watch::Sender::new(None),
+            events: broadcast::Sender::new(16),
+            end_watch: Once::new(),
+            shutdown: watch::Sender::new(false),
+            incognito: AtomicBool::new(false),
+            plays: AtomicU64::new(0),
+            cookies,
+            cookies_file: paths.data_dir.join("cookies.txt"),
+        })
+    }
+
+    /// Returns the duration, channel and thumbnail of a song, from the cache when they
+    /// were fetched recently. Local files have none.
+    pub async fn metadata(&self, song_id: &SongId) -> Result<Option<SongMetadata>, BackendError> {
+        let song = Song::new(String::new(), song_id.clone(), Vec::new());
+        if let Source::Local(_) = song.source() {
+            return Ok(None);
+        }
+        if let Ok(Some(cached)) = self.metadata.get(song_id) {
+            return Ok(Some(cached));
+        }
+        let metadata = self
+            .yt
+            .fetch_metadata(song_id)
+            .await
+            .map_err(|e| BackendError::YoutubeFetch(e.to_string()))?;
+        self.metadata
+            .set(song_id, metadata)
+            .map(Some)
+            .map_err(|e| BackendError::HistoryError(e.to_string()))
+    }
+
+    /// Returns the lyrics of a song, from the cache when they were looked up recently.
+    /// Songs without lyrics are cached too, so they are not looked up again.
+    pub async fn lyrics(&self, song: &Song) -> Result<Lyrics, BackendError> {
+        if let Ok(Some(lyrics)) = self.metadata.get_lyrics(&song.song_id) {
+            return Ok(lyrics);
+        }
+        // The length tells apart versions of a song, such as live and album cuts
+        let duration = self
+            .metadata(&song.song_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|metadata| metadata.duration);
+        let lyrics = crate::lyrics::fetch(song, duration)
+            .await
+            .map_err(BackendError::LyricsError)?;
+        let _ = self.metadata.set_lyrics(&song.song_id, &lyrics); // Fetched again next time
+        Ok(lyrics)
+    }
+
+    /// Returns whether a cookies source was configured.
+    pub fn has_cookies(&self) -> bool {
+        self.cookies.is_some()
+    }
+
+    /// Reads the cookies source again and hands the cookies to mpv and the YouTube client,
+    /// e.g. after the session in the browser was renewed.
+    ///
+    /// # Returns
+    /// * `Result<usize, BackendError>` - The number of YouTube cookies loaded.
+    pub async fn reload_cookies(&self) -> Result<usize, BackendError> {
+        let Some(source) = self.cookies.clone() else {
+            return Err(BackendError::CookieError(
+                "no cookies file or browser configured".to_string(),
+            ));
+        };
+        let cookies_error = |e: &dyn std::fmt::Display| BackendError::CookieError(e.to_string());
+        // Browser stores are databases, read them off the async workers
+        let jar = tokio::task::spawn_blocking(move || CookieJar::load(&source))
+            .await
+            .map_err(|e| cookies_error(&e))?
+            .map_err(|e| cookies_error(&e))?;
+        if jar.is_empty() {
+            return Err(BackendError::CookieError(
+                "no YouTube cookies found, sign in to YouTube first".to_string(),
+            ));
+        }
+        jar.write_netscape(&self.cookies_file)
+            .map_err(|e| cookies_error(&e))?;
+        self.player.set_cookies_file(&self.cookies_file)?;
+        self.yt
+            .set_cookies(&jar)
+            .await
+            .map_err(|e| cookies_error(&e))?;
+        Ok(jar.cookies.len())
+    }
+
+    /// Returns the ID of the song handed to the player last, if any.
+    ///
+    /// Only the ID is cloned, so list renderers can call this every frame.
+    pub fn current_song_id(&self) -> Option<SongId> {
+        self.song
+            .lock()
+            .ok()
+            .and_then(|song| song.as_ref().map(|song| song.song_id.clone()))
+    }
+
+    /// Keeps songs played from now on out of the history, and away from scrobbling.
+    pub fn set_incognito(&self, incognito: bool) {
+        self.incognito.store(incognito, Ordering::Relaxed);
+    }
+
+    /// Returns whether plays are currently kept out of the history.
+    pub fn is_incognito(&self) -> bool {
+        self.incognito.load(Ordering::Relaxed)
+    }
+
+    /// Subscribes to song changes, for integrations that follow what is playing.
+    ///
+    /// The receiver starts out holding the current song, if any.
+    pub fn subscribe(&self) -> watch::Receiver<Option<Song>> {
+        self.song_changes.subscribe()
+    }
+
+    /// Runs a background task until it finishes or `shutdown` is called.
+    pub fn spawn_until_shutdown<F>(&self, task: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut stopping = self.shutdown.subscribe();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = task => (),
+                _ = stopping.wait_for(|stopping| *stopping) => (),
+            }
+        })
+    }
+
+    /// Stops the tasks started with `spawn_until_shutdown`, quits mpv and flushes the
+    /// database. Called once before the process exits; playback is over afterwards.
+    pub fn shutdown(&self) -> Result<(), BackendError> {
+        self.shutdown.send_replace(true);
+        let _ = self.player.quit(); // mpv over IPC may hang up before replying
+        self.history
+            .flush()
+            .map_err(|e| BackendError::HistoryError(e.to_string()))
+    }
+
+    /// Subscribes to playback events, for views that show the player's state.
+    pub fn events(&self) -> broadcast::Receiver<PlayerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Plays a song by fetching its URL from YouTube and passing it to the player.
+    /// A finished download of the song is played from disk instead.
+    /// When another song is requested before this one is loaded or recorded in history,
+    /// it gives way and returns `BackendError::Superseded`.
+    ///
+    /// # Arguments
+    /// * `song` - The song to be played.
+    ///
+    /// # Returns
+    /// * `Result<(), BackendError>` - Returns `Ok(())` on success or an error on failure.
+    pub async fn play_music(&self, song: Song) -> Result<(), BackendError> {
+        self.play_source(song.playback_source(), song).await
+    }
+
+    /// Plays audio from any source, showing and recording `meta` for it, so streams
+    /// resolved elsewhere and direct links are listed like any other song.
+    /// Superseded requests behave as in `play_music`.
+    ///
+    /// # Arguments
+    /// * `source` - Where the audio comes from.
+    /// * `meta` - The song shown in the player and added to the history.
+    ///
+    /// # Returns
+    /// * `Result<(), BackendError>` - Returns `Ok(())` on success or an error on failure.
+    pub async fn play_source(
+        &self,
+        source: PlaybackSource,
+        meta: Song,
+    ) -> Result<(), BackendError> {
+        self.end_watch.call_once(|| {
+            self.spawn_until_shutdown(watch_end(self.player.downgrade(), self.events.clone()));
+        });
+        let generation = self.plays.fetch_add(1, Ordering::SeqCst) + 1;
+        let result = self.load(source, meta, generation).await;
+        match &result {
+            Err(BackendError::Superseded) => (),
+            // Failures of requests that were overtaken say nothing about what is playing
+            Err(e) if self.plays.load(Ordering::SeqCst) == generation => {
+                let _ = self.events.send(PlayerEvent::Error(e.to_string()));
+            }
+            _ => (),
+        }
+        result
+    }
+
+    /// Plays a song YouTube suggests after the current one; there is no queue to skip in.
+    ///
+    /// # Returns
+    /// * `Result<Song, BackendError>` - The song that started, or an error on failure.
+    pub async fn play_related(&self) -> Result<Song, BackendError> {
+        let current = self
+            .current_song_id()
+            .ok_or_else(|| BackendError::PlaybackError("nothing is playing".to_string()))?;
+        if let Source::Local(_) = Song::new(String::new(), current.clone(), Vec::new()).source() {
+            return Err(BackendError::PlaybackError(
+                "local files have no related songs".to_string(),
+            ));
+        }
+        let related = self
+            .yt
+            .fetch_related_song(current.clone())
+            .await
+            .map_err(|e| BackendError::YoutubeFetch(e.to_string()))?;
+        let ((name, id), artists) = related
+            .into_iter()
+            .find(|((_, id), _)| *id != current)
+            .ok_or_else(|| BackendError::PlaybackError("no related songs found".to_string()))?;
+        let song = Song::new(name, id, artists);
+        self.play_music(song.clone()).await?;
+        Ok(song)
+    }
+
+    /// Resolves and loads a source for the play request with the given generation.
+    async fn load(
+        &self,
+        source: PlaybackSource,
+        song: Song,
+        generation: u64,
+    ) -> Result<(), BackendError> {
+        let superseded = || self.plays.load(Ordering::SeqCst) != generation;
+        let url = self.resolve(source).await?;
+        let song = self.with_cached_details(song);
+
+        // Hand the song to mpv while holding the current song, so that of several
+        // requests racing here only the latest one is loaded
+        {
+            let mut current_song = self
+                .song
+                .lock()
+                .map_err(|e| BackendError::MutexPoisoned(e.to_string()))?;
+            if superseded() {
+                return Err(BackendError::Superseded);
+            }
+            *current_song = Some(song.clone());
+            self.player.play(&url).map_err(BackendError::Mpv)?;
+            self.song_changes.send_replace(Some(song.clone()));
+            let _ = self.events.send(PlayerEvent::SongStarted(song.clone()));
+        }
+
+        if self.is_incognito() {
+            return Ok(());
+        }
+        if superseded() {
+            return Err(BackendError::Superseded); // Replaced before it was heard
+        }
+
+        // Add the song to history
+        self.history
+            .add_entry(&HistoryEntry::from(song))
+            .map_err(|e| BackendError::HistoryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Fills in the length and cover art of a song that came without them, when they
+    /// were fetched for it before.
+    fn with_cached_details(&self, song: Song) -> Song {
+        if song.duration_secs.is_some() && song.thumbnail.is_some() {
+            return song;
+        }
+        let Ok(Some(metadata)) = self.metadata.get(&song.song_id) else {
+            return song;
+        };
+        let duration_secs = song.duration_secs.or(metadata.duration.map(u64::from));
+        let thumbnail = song.thumbnail.clone().or(metadata.thumbnail);
+        song.with_details(duration_secs, thumbnail)
+    }
+
+    /// Turns a source into something mpv can open: a path, or a stream URL fetched
+    /// with retries for YouTube songs that were not downloaded.
+    async fn resolve(&self, source: PlaybackSource) -> Result<String, BackendError> {
+        const MAX_RETRIES: i32 = 8;
+        let id = match source {
+            PlaybackSource::Url(url) => return Ok(url),
+            PlaybackSource::File(path) => {
+                if !path.exists() {
+                    return Err(BackendError::PlaybackError(format!(
+                        "{} no longer exists",
+                        path.display()
+                    )));
+                }
+                return Ok(path.to_string_lossy().into_owned());
+            }
+            PlaybackSource::YoutubeId(id) => id,
+        };
+        if let Some(path) = self.downloads.local_file(&id) {
+            return Ok(path.to_string_lossy().into_owned());
+        }
+        let mut attempts = 0;
+        loop {
+            match self.yt.fetch_song_url(&id).await {
+                Ok(url) => return Ok(url),
+                Err(e) if e.is_transient() && attempts < MAX_RETRIES => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                Err(e) => return Err(BackendError::YoutubeFetch(e.to_string())),
+            }
+        }
+    }
+}
+
+/// Announces when mpv goes idle after playing, i.e. when a song ended.
+/// Stops once the player is dropped, so an mpv process is not kept running.
+async fn watch_end(player: WeakPlayer, events: broadcast::Sender<PlayerEvent>) {
+    let mut idle = true;
+    loop {
+        let Some(now_idle) = player.upgrade().map(|player| player.is_idle()) else {
+            return;
+        };
+        if now_idle && !idle {
+            let _ = events.send(PlayerEvent::SongEnded);
+        }
+        idle = now_idle;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestBackend;
+
+    fn history(h: &TestBackend) -> Vec<SongId> {
+        let history = h.backend.history.get_history().unwrap();
+        history.into_iter().map(|entry| entry.song_id).collect()
+    }
+
+    fn stream(id: &str) -> (PlaybackSource, Song) {
+        let source = PlaybackSource::Url(format!("https://example.com/{}.opus", id));
+        (
+            source,
+            Song::new(format!("Song {}", id), id.to_string(), Vec::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn played_file_is_loaded_and_recorded() {
+        let h = TestBackend::new();
+        let path = h.dir.path().join("song.mp3");
+        std::fs::write(&path, b"").unwrap();
+        let song = Song::new("Song".to_string(), path.display().to_string(), Vec::new());
+
+        h.backend.play_music(song).await.unwrap();
+        assert_eq!(h.mpv.loaded(), [path.display().to_string()]);
+        assert_eq!(history(&h), [path.display().to_string()]);
+        assert_eq!(
+            h.backend.current_song_id(),
+            Some(path.display().to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_neither_loaded_nor_recorded() {
+        let h = TestBackend::new();
+        let path = h.dir.path().join("gone.mp3");
+        let song = Song::new("Gone".to_string(), path.display().to_string(), Vec::new());
+
+        let result = h.backend.play_music(song).await;
+        assert!(matches!(result, Err(BackendError::PlaybackError(_))));
+        assert!(h.mpv.loaded().is_empty());
+        assert!(history(&h).is_empty());
+        assert_eq!(h.backend.current_song_id(), None);
+    }
+
+    #[tokio::test]
+    async fn incognito_plays_stay_out_of_history() {
+        let h = TestBackend::new();
+        let (source, song) = stream("a");
+        h.backend.set_incognito(true);
+
+        h.backend.play_source(source, song).await.unwrap();
+        assert_eq!(h.mpv.loaded().len(), 1);
+        assert!(history(&h).is_empty());
+    }
+
+    #[tokio::test]
+    async fn overlapping_plays_record_only_the_last_song() {
+        for later_finishes_first in [true, false] {
+            let h = TestBackend::new();
+            let (first_source, first_song) = stream("first");
+            let (last_source, last_song) = stream("last");
+            // Both are requested before either is loaded
+            let first = h.backend.plays.fetch_add(1, Ordering::SeqCst) + 1;
+            let last = h.backend.plays.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let (first_result, last_result) = if later_finishes_first {
+                let last_result = h.backend.load(last_source, last_song, last).await;
+                let first_result = h.backend.load(first_source, first_song, first).await;
+                (first_result, last_result)
+            } else {
+                let first_result = h.backend.load(first_source, first_song, first).await;
+                let last_result = h.backend.load(last_source, last_song, last).await;
+                (first_result, last_result)
+            };
+            assert!(matches!(first_result, Err(BackendError::Superseded)));
+            assert!(last_result.is_ok());
+            assert_eq!(h.mpv.loaded(), ["https://example.com/last.opus"]);
+            assert_eq!(history(&h), ["last"]);
+            assert_eq!(h.backend.current_song_id().as_deref(), Some("last"));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// This file loads YouTube cookies from a cookies.txt file or a browser's cookie store
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Domains whose cookies are kept; the rest of a browser's store is never read into memory.
+const DOMAINS: &[&str] = &["youtube.com", "google.com"];
+
+/// Enum representing possible errors while loading cookies.
+#[derive(Debug, thiserror::Error)]
+pub enum CookieError {
+    #[error("Cannot read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Invalid cookies file, line {0} is not in Netscape format")]
+    Parse(usize),
+    #[error("Cannot read the {0} cookie store: {1}")]
+    Browser(String, String),
+    #[error("Reading cookies from {0} is not supported, export a cookies.txt file instead")]
+    Unsupported(String),
+}
+
+/// Where cookies are loaded from.
+#[derive(Clone, Debug)]
+pub enum CookieSource {
+    /// A Netscape-format cookies.txt, as exported by yt-dlp or browser extensions.
+    File(PathBuf),
+    /// A browser's own cookie store, e.g. "firefox".
+    Browser(String),
+}
+
+/// A single cookie, with the columns of a cookies.txt line.
+#[derive(Clone, Debug)]
+pub struct Cookie {
+    pub domain: String,
+    pub include_subdomains: bool,
+    pub path: String,
+    pub secure: bool,
+    pub expires: u64, // Unix time, 0 for session cookies
+    pub name: String,
+    pub value: String,
+}
+
+/// The YouTube and Google cookies of a source, shared by mpv and the YouTube client.
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    pub cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Loads the cookies of the given source.
+    pub fn load(source: &CookieSource) -> Result<Self, CookieError> {
+        match source {
+            CookieSource::File(path) => {
+                let text =
+                    fs::read_to_string(path).map_err(|e| CookieError::Io(path.clone(), e))?;
+                Self::parse_netscape(&text)
+            }
+            CookieSource::Browser(browser) => match browser.to_lowercase().as_str() {
+                "firefox" => firefox_cookies(),
+                _ => Err(CookieError::Unsupported(browser.clone())),
+            },
+        }
+    }
+
+    /// Parses a Netscape-format cookies file, skipping comments and other domains.
+    pub fn parse_netscape(text: &str) -> Result<Self, CookieError> {
+        let mut cookies = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            // curl marks HttpOnly cookies with a prefix on an otherwise commented line
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+                return Err(CookieError::Parse(number + 1));
+            };
+            let cookie = Cookie {
+                domain: domain.to_string(),
+                include_subdomains: subdomains.eq_ignore_ascii_case("TRUE"),
+                path: path.to_string(),
+                secure: secure.eq_ignore_ascii_case("TRUE"),
+                expires: expires.parse().unwrap_or(0),
+                name: name.to_string(),
+                value: value.to_string(),
+            };
+            if is_youtube(&cookie.domain) {
+                cookies.push(cookie);
+            }
+        }
+        Ok(CookieJar { cookies })
+    }
+
+    /// Formats the cookies as a Netscape-format file, readable by mpv and yt-dlp.
+    pub fn to_netscape(&self) -> String {
+        let mut text = String::from("# Netscape HTTP Cookie File\n");
+        let flag = |set: bool| if set { "TRUE" } else { "FALSE" };
+        for cookie in &self.cookies {
+            text.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                cookie.domain,
+                flag(cookie.include_subdomains),
+                cookie.path,
+                flag(cookie.secure),
+                cookie.expires,
+                cookie.name,
+                cookie.value
+            ));
+        }
+        text
+    }
+
+    /// Writes the cookies to a file only the current user can read. On other than Unix
+    /// the file gets the default permissions of its directory.
+    pub fn write_netscape(&self, path: &Path) -> Result<(), CookieError> {
+        use std::io::Write;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(path)
+            .map_err(|e| CookieError::Io(path.to_path_buf(), e))?;
+        file.write_all(self.to_netscape().as_bytes())
+            .map_err(|e| CookieError::Io(path.to_path_buf(), e))
+    }
+
+    /// Returns whether no cookies were found.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+fn is_youtube(domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    DOMAINS
+        .iter()
+        .any(|kept| domain == *kept || domain.ends_with(&format!(".{}", kept)))
+}
+
+/// Reads the cookies of Firefox's default profile.
+///
+/// The database is locked while Firefox runs, so a copy of it is read instead,
+/// along with its write-ahead log holding the latest changes.
+fn firefox_cookies() -> Result<CookieJar, CookieError> {
+    let browser_error =
+        |e: &dyn std::fmt::Display| CookieError::Browser("firefox".into(), e.to_string());
+    let profile = firefox_profile().ok_or_else(|| browser_error(&"no profile found"))?;
+    let copy = tempfile::tempdir().map_err(|e| browser_error(&e))?;
+    for file in ["cookies.sqlite", "cookies.sqlite-wal"] {
+        match fs::copy(profile.join(file), copy.path().join(file)) {
+            Err(e) if file.ends_with("-wal") && e.kind() == std::io::ErrorKind::NotFound => (),
+            copied => {
+                copied.map_err(|e| browser_error(&e))?;
+            }
+        }
+    }
+
+    let db = rusqlite::Connection::open_with_flags(
+        copy.path().join("cookies.sqlite"),
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )
+    .map_err(|e| browser_error(&e))?;
+    let mut query = db
+        .prepare("SELECT host, path, isSecure, expiry, name, value FROM moz_cookies")
+        .map_err(|e| browser_error(&e))?;
+    let rows = query
+        .query_map([], |row| {
+            let host: String = row.get(0)?;
+            Ok(Cookie {
+                include_subdomains: host.starts_with('.'),
+                domain: host,
+                path: row.get(1)?,
+                secure: row.get::<_, i64>(2)? != 0,
+                expires: row.get::<_, i64>(3)?.max(0) as u64,
+                name: row.get(4)?,
+                value: row.get(5)?,
+            })
+        })
+        .map_err(|e| browser_error(&e))?;
+
+    let mut cookies = Vec::new();
+    for cookie in rows {
+        let cookie = cookie.map_err(|e| browser_error(&e))?;
+        if is_youtube(&cookie.domain) {
+            cookies.push(cookie);
+        }
+    }
+    Ok(CookieJar { cookies })
+}
+
+/// Finds the profile Firefox starts with, from the `profiles.ini` in its data directory.
+fn firefox_profile() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let root = [
+        home.join(".mozilla/firefox"),
+        home.join("Library/Application Support/Firefox"),
+        home.join("snap/firefox/common/.mozilla/firefox"),
+    ]
+    .into_iter()
+    .find(|root| root.join("profiles.ini").exists())?;
+    let ini = fs::read_to_string(root.join("profiles.ini")).ok()?;
+
+    // Installs record their profile in an [Install...] section, older setups mark it Default=1
+    let mut install_default = None;
+    let mut marked_default = None;
+    let mut first = None;
+    let (mut section, mut path, mut relative, mut default) = (String::new(), None, true, false);
+    for line in ini.lines().map(str::trim).chain(std::iter::once("[end]")) {
+        if line.starts_with('[') {
+            if section.starts_with("Profile")
+                && let Some(path) = path.take()
+            {
+                let path = if relative {
+                    root.join(&path)
+                } else {
+                    PathBuf::from(path)
+                };
+                if default {
+                    marked_default.get_or_insert(path.clone());
+                }
+                first.get_or_insert(path);
+            }
+            section = line.trim_matches(['[', ']']).to_string();
+            (relative, default) = (true, false);
+        } else if let Some((key, value)) = line.split_once('=') {
+            match key {
+                "Default" if section.starts_with("Install") => {
+                    install_default.get_or_insert(root.join(value));
+                }
+                "Path" => path = Some(value.to_string()),
+                "IsRelative" => relative = value == "1",
+                "Default" => default = value == "1",
+                _ => (),
+            }
+        }
+    }
+    install_default.or(marked_default).or(first)
+}
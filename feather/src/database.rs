@@ -1,9 +1,13 @@
 // This file manages the history database and contains all necessary functions related to history management
+use crate::paths::Paths;
 use crate::{ArtistName, SongId, SongName};
 use serde::{Deserialize, Serialize};
 use sled::Db;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 /// Represents a history entry for a song that has been played.
@@ -32,9 +36,24 @@ impl HistoryEntry {
     }
 }
 
+/// Most entries the history keeps; the oldest give way.
+pub const HISTORY_LIMIT: usize = 50;
+
+const DEFAULT_TREE: &[u8] = b"__sled__default"; // Where sled keeps the history entries
+const META_TREE: &str = "meta"; // Bookkeeping about the database itself
+const LAST_FLUSH: &[u8] = b"last_flush";
+
+/// Contents and size of the history database, as reported by `HistoryDB::stats`.
+pub struct DbStats {
+    pub size_on_disk: u64, // Bytes taken by the database, all trees together
+    pub trees: Vec<(String, usize)>, // Entries per tree, the history itself first
+    pub last_flush: Option<u64>, // Unix time of the last `flush`, None before the first
+}
+
 /// Database handler for managing song history.
 pub struct HistoryDB {
-    db: Db, // Sled database instance
+    db: Db,                // Sled database instance
+    generation: AtomicU64, // Bumped on every change to the history entries
 }
 
 /// Represents possible errors that can occur in history operations.
@@ -46,47 +65,125 @@ pub enum HistoryError {
     SerializationError(#[from] bincode::Error), // Errors during serialization/deserialization
     #[error("Basic error: {0}")]
     Error(Box<dyn std::error::Error>), // Generic error wrapper
+    #[error("Another Feather instance appears to be using the database")]
+    InUse, // Another process holds the database's lock
+}
+
+impl HistoryError {
+    /// Tells a database locked by another process apart from other failures to open it.
+    /// sled reports the lock only as an I/O error whose message starts as below.
+    fn from_open(e: sled::Error) -> Self {
+        match &e {
+            sled::Error::Io(io) if io.to_string().starts_with("could not acquire lock") => {
+                HistoryError::InUse
+            }
+            _ => HistoryError::DbError(e),
+        }
+    }
 }
 
 impl HistoryDB {
-    pub fn new() -> Result<Self, sled::Error> {
-        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
-        path.push("Feather/history_db");
+    /// Opens the history database in the default data directory.
+    pub fn new() -> Result<Self, HistoryError> {
+        Self::new_at(&Paths::default().data_dir)
+    }
+
+    /// Opens the history database under the given base directory.
+    /// Fails with `HistoryError::InUse` while another process has it open.
+    pub fn new_at(base: &Path) -> Result<Self, HistoryError> {
+        let path = base.join("history_db");
+        std::fs::create_dir_all(&path).map_err(sled::Error::from)?; // Fresh installs have no Feather directory yet
 
         let db = sled::Config::new()
             .path(path)
             .cache_capacity(256 * 1024)
             .use_compression(true)
-            .open()?;
+            .open()
+            .map_err(HistoryError::from_open)?;
+
+        Ok(HistoryDB {
+            db,
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    /// Writes everything changed so far to disk, e.g. before the process exits.
+    pub fn flush(&self) -> Result<(), HistoryError> {
+        self.db
+            .open_tree(META_TREE)?
+            .insert(LAST_FLUSH, &now().to_be_bytes())?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Counts the entries of every tree and measures the database on disk.
+    pub fn stats(&self) -> Result<DbStats, HistoryError> {
+        let mut trees = vec![("history".to_string(), self.db.len())];
+        for name in self.db.tree_names() {
+            // The default tree is the history, listed above
+            if name.as_ref() == DEFAULT_TREE || name.as_ref() == META_TREE.as_bytes() {
+                continue;
+            }
+            let len = self.db.open_tree(&name)?.len();
+            trees.push((String::from_utf8_lossy(&name).into_owned(), len));
+        }
+        let last_flush = self
+            .db
+            .open_tree(META_TREE)?
+            .get(LAST_FLUSH)?
+            .and_then(|value| <[u8; 8]>::try_from(value.as_ref()).ok())
+            .map(u64::from_be_bytes);
+        Ok(DbStats {
+            size_on_disk: self.db.size_on_disk()?,
+            trees,
+            last_flush,
+        })
+    }
 
-        Ok(HistoryDB { db })
+    /// Returns a counter that changes whenever entries are added or removed, so views
+    /// can keep what they read until it moves.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
     }
 
     /// Adds a new entry to the history database.
-    /// Limits the total stored entries to 50.
+    /// Limits the total stored entries to `HISTORY_LIMIT`.
     pub fn add_entry(&self, entry: &HistoryEntry) -> Result<(), HistoryError> {
         let key = entry.song_id.as_bytes();
         let value = bincode::serialize(entry)?;
         self.db.insert(key, value)?;
-        self.limit_history_size(50)?;
-        Ok(())
+        let limited = self.limit_history_size(HISTORY_LIMIT);
+        self.generation.fetch_add(1, Ordering::SeqCst); // After trimming, so readers see both
+        limited
     }
 
     /// Ensures the history database does not exceed `max_size` entries.
-    /// Removes the oldest entries if necessary.
+    /// Removes the oldest entries if necessary, unreadable ones first.
     pub fn limit_history_size(&self, max_size: usize) -> Result<(), HistoryError> {
-        while self.db.len() > max_size {
-            if let Some((key, _)) = self.db.first()? {
-                self.db.remove(key)?;
-            }
+        let excess = self.db.len().saturating_sub(max_size);
+        if excess == 0 {
+            return Ok(());
+        }
+        // Entries are keyed by song ID, so their age is only known from their contents
+        let mut entries = Vec::with_capacity(self.db.len());
+        for item in self.db.iter() {
+            let (key, value) = item?;
+            let time_stamp = bincode::deserialize::<HistoryEntry>(&value)
+                .map(|entry| entry.time_stamp)
+                .unwrap_or(0);
+            entries.push((time_stamp, key));
+        }
+        entries.sort_unstable();
+        for (_, key) in entries.into_iter().take(excess) {
+            self.db.remove(key)?;
         }
         Ok(())
     }
 
-    /// Retrieves up to 50 history entries, sorted by most recent first.
+    /// Retrieves up to `HISTORY_LIMIT` history entries, sorted by most recent first.
     pub fn get_history(&self) -> Result<Vec<HistoryEntry>, HistoryError> {
-        let mut history = Vec::with_capacity(self.db.len().min(50)); // Pre-allocate vector
-        for item in self.db.iter().take(50) {
+        let mut history = Vec::with_capacity(self.db.len().min(HISTORY_LIMIT)); // Pre-allocate vector
+        for item in self.db.iter().take(HISTORY_LIMIT) {
             let (_, value) = item?;
             if let Ok(entry) = bincode::deserialize::<HistoryEntry>(&value) {
                 history.push(entry);
@@ -96,18 +193,84 @@ impl HistoryDB {
         Ok(history)
     }
 
+    /// Merges entries from elsewhere, such as an archive, into the history.
+    /// Of two entries for a song the more recent play wins, and of all entries the
+    /// `HISTORY_LIMIT` most recent are kept. The change is applied in one batch, so it
+    /// either happens completely or not at all. Returns how many entries were taken over.
+    pub fn merge(&self, entries: Vec<HistoryEntry>) -> Result<usize, HistoryError> {
+        let current = self.get_history()?;
+        let mut newest: HashMap<SongId, (HistoryEntry, bool)> = HashMap::new(); // bool: incoming
+        for (entry, incoming) in current
+            .into_iter()
+            .map(|entry| (entry, false))
+            .chain(entries.into_iter().map(|entry| (entry, true)))
+        {
+            match newest.get(&entry.song_id) {
+                Some((kept, _)) if kept.time_stamp >= entry.time_stamp => (),
+                _ => {
+                    newest.insert(entry.song_id.clone(), (entry, incoming));
+                }
+            }
+        }
+        let mut kept: Vec<(HistoryEntry, bool)> = newest.into_values().collect();
+        kept.sort_unstable_by_key(|(entry, _)| std::cmp::Reverse(entry.time_stamp));
+        let dropped = kept.split_off(kept.len().min(HISTORY_LIMIT));
+
+        let mut batch = sled::Batch::default();
+        for (entry, _) in &dropped {
+            batch.remove(entry.song_id.as_bytes());
+        }
+        let mut merged = 0;
+        for (entry, incoming) in &kept {
+            if *incoming {
+                batch.insert(entry.song_id.as_bytes(), bincode::serialize(entry)?);
+                merged += 1;
+            }
+        }
+        self.db.apply_batch(batch)?;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(merged)
+    }
+
     /// Deletes a specific history entry by song ID.
     pub fn delete_entry(&self, song_id: &str) -> Result<(), HistoryError> {
         self.db.remove(song_id.as_bytes())?; // Convert song ID to bytes
+        self.generation.fetch_add(1, Ordering::SeqCst);
         Ok(())
     }
 
     /// Clears all history entries from the database.
     pub fn clear_history(&self) -> Result<(), HistoryError> {
         self.db.clear()?;
+        self.generation.fetch_add(1, Ordering::SeqCst);
         Ok(())
     }
 
+    /// Opens the queue of listens waiting to be scrobbled, stored beside the history.
+    pub fn scrobble_queue(&self) -> Result<ScrobbleQueue, HistoryError> {
+        Ok(ScrobbleQueue {
+            tree: self.db.open_tree("scrobble_queue")?,
+        })
+    }
+
+    /// Opens the record of downloaded songs, stored beside the history.
+    /// Clones of the handle share its change counter, so open it once and clone it.
+    pub fn downloads(&self) -> Result<Downloads, HistoryError> {
+        Ok(Downloads {
+            tree: self.db.open_tree("downloads")?,
+            generation: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Opens the cache of song metadata, stored beside the history.
+    pub fn metadata_cache(&self) -> Result<MetadataCache, HistoryError> {
+        Ok(MetadataCache {
+            tree: self.db.open_tree("metadata_cache")?,
+            lyrics: self.db.open_tree("lyrics_cache")?,
+            ttl: METADATA_TTL,
+        })
+    }
+
     /// Retrieves the most recently played song's ID, if available.
     pub fn get_last_played_song(&self) -> Result<Option<SongId>, HistoryError> {
         if let Some((_, last_entry)) = self.db.last()? {
@@ -119,6 +282,234 @@ impl HistoryDB {
     }
 }
 
+/// A finished listen that still has to be submitted to a scrobbling service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Listen {
+    pub song_name: SongName,          // Name of the song
+    pub song_id: SongId,              // Unique identifier for the song
+    pub artist_name: Vec<ArtistName>, // List of artists associated with the song
+    pub listened_at: u64,             // Unix time the song started playing
+}
+
+/// Listens that could not be submitted yet, kept in their own tree of the history database
+/// so they survive restarts and are retried in the order they happened.
+pub struct ScrobbleQueue {
+    tree: sled::Tree, // Keyed by start time, then song ID
+}
+
+impl ScrobbleQueue {
+    /// Adds a listen to the end of the queue.
+    pub fn push(&self, listen: &Listen) -> Result<(), HistoryError> {
+        let mut key = listen.listened_at.to_be_bytes().to_vec();
+        key.extend_from_slice(listen.song_id.as_bytes());
+        self.tree.insert(key, bincode::serialize(listen)?)?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` queued listens, oldest first, with the keys to remove them by.
+    pub fn pending(&self, limit: usize) -> Result<Vec<(sled::IVec, Listen)>, HistoryError> {
+        let mut listens = Vec::new();
+        for item in self.tree.iter().take(limit) {
+            let (key, value) = item?;
+            if let Ok(listen) = bincode::deserialize::<Listen>(&value) {
+                listens.push((key, listen));
+            } else {
+                self.tree.remove(key)?; // Unreadable entries would block the queue forever
+            }
+        }
+        Ok(listens)
+    }
+
+    /// Removes a listen once it has been submitted.
+    pub fn remove(&self, key: &[u8]) -> Result<(), HistoryError> {
+        self.tree.remove(key)?;
+        Ok(())
+    }
+}
+
+/// Progress of a song download.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DownloadStatus {
+    Queued,      // Waiting for the stream URL
+    Downloading, // Audio is being written to disk
+    Done,        // The file is complete and can be played offline
+    Failed,      // The download stopped with an error
+}
+
+/// A song saved, or being saved, for offline playback.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Download {
+    pub song_name: SongName,          // Name of the song
+    pub song_id: SongId,              // Unique identifier for the song
+    pub artist_name: Vec<ArtistName>, // List of artists associated with the song
+    pub path: PathBuf,                // Where the audio file is written
+    pub status: DownloadStatus,       // How far the download got
+}
+
+/// How long fetched song metadata is trusted before it is fetched again.
+pub const METADATA_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Details of a song beyond its name and artists, listed by some searches.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SongMetadata {
+    pub duration: Option<u32>,     // Length in seconds
+    pub channel: Option<String>,   // Channel that uploaded the song
+    pub thumbnail: Option<String>, // URL of the largest thumbnail
+    pub fetched_at: u64,           // Unix time the details were fetched
+}
+
+/// Lyrics of a song, as found online.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Lyrics {
+    Synced(Vec<(f64, String)>), // Lines with the second they are sung at, in order
+    Plain(String),              // Text without timing
+    Missing,                    // Looked up, but none were found
+}
+
+/// Song metadata keyed by song ID, kept in its own tree of the history database.
+/// Entries older than the TTL count as missing until they are fetched again.
+#[derive(Clone)]
+pub struct MetadataCache {
+    tree: sled::Tree,
+    lyrics: sled::Tree, // Lyrics, kept apart as they are far larger than the rest
+    ttl: Duration,
+}
+
+impl MetadataCache {
+    /// Returns the cached metadata of a song, unless it is missing or stale.
+    pub fn get(&self, song_id: &str) -> Result<Option<SongMetadata>, HistoryError> {
+        let Some(value) = self.tree.get(song_id.as_bytes())? else {
+            return Ok(None);
+        };
+        let metadata: SongMetadata = bincode::deserialize(&value)?;
+        Ok(Some(metadata).filter(|metadata| !self.is_stale(metadata.fetched_at)))
+    }
+
+    /// Stores freshly fetched metadata, returning it stamped with the current time.
+    pub fn set(
+        &self,
+        song_id: &str,
+        mut metadata: SongMetadata,
+    ) -> Result<SongMetadata, HistoryError> {
+        metadata.fetched_at = now();
+        self.tree
+            .insert(song_id.as_bytes(), bincode::serialize(&metadata)?)?;
+        Ok(metadata)
+    }
+
+    /// Removes stale and unreadable entries, returning how many were removed.
+    pub fn prune(&self) -> Result<usize, HistoryError> {
+        let mut removed = 0;
+        for item in self.lyrics.iter() {
+            let (key, value) = item?;
+            let stale = bincode::deserialize::<(u64, Lyrics)>(&value)
+                .ok()
+                .is_none_or(|(fetched_at, _)| self.is_stale(fetched_at));
+            if stale {
+                self.lyrics.remove(key)?;
+                removed += 1;
+            }
+        }
+        for item in self.tree.iter() {
+            let (key, value) = item?;
+            let stale = bincode::deserialize::<SongMetadata>(&value)
+                .ok()
+                .is_none_or(|metadata| self.is_stale(metadata.fetched_at));
+            if stale {
+                self.tree.remove(key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Returns the cached lyrics of a song, unless they are missing or stale.
+    pub fn get_lyrics(&self, song_id: &str) -> Result<Option<Lyrics>, HistoryError> {
+        let Some(value) = self.lyrics.get(song_id.as_bytes())? else {
+            return Ok(None);
+        };
+        let (fetched_at, lyrics): (u64, Lyrics) = bincode::deserialize(&value)?;
+        Ok(Some(lyrics).filter(|_| !self.is_stale(fetched_at)))
+    }
+
+    /// Stores freshly fetched lyrics, including the finding that there are none.
+    pub fn set_lyrics(&self, song_id: &str, lyrics: &Lyrics) -> Result<(), HistoryError> {
+        self.lyrics
+            .insert(song_id.as_bytes(), bincode::serialize(&(now(), lyrics))?)?;
+        Ok(())
+    }
+
+    fn is_stale(&self, fetched_at: u64) -> bool {
+        now().saturating_sub(fetched_at) > self.ttl.as_secs()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Downloaded songs keyed by song ID, kept in their own tree of the history database.
+#[derive(Clone)]
+pub struct Downloads {
+    tree: sled::Tree,
+    generation: Arc<AtomicU64>, // Bumped on every change, as in `HistoryDB`
+}
+
+impl Downloads {
+    /// Returns a counter that changes whenever a download is recorded, updated or removed.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Adds or updates the record of a download.
+    pub fn set(&self, download: &Download) -> Result<(), HistoryError> {
+        self.tree
+            .insert(download.song_id.as_bytes(), bincode::serialize(download)?)?;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the record for a song, if it was ever downloaded.
+    pub fn get(&self, song_id: &str) -> Result<Option<Download>, HistoryError> {
+        match self.tree.get(song_id.as_bytes())? {
+            Some(value) => Ok(Some(bincode::deserialize(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the local file of a song whose download finished and is still on disk.
+    pub fn local_file(&self, song_id: &str) -> Option<PathBuf> {
+        self.get(song_id)
+            .ok()
+            .flatten()
+            .filter(|download| download.status == DownloadStatus::Done && download.path.exists())
+            .map(|download| download.path)
+    }
+
+    /// Returns every recorded download, sorted by song name.
+    pub fn list(&self) -> Result<Vec<Download>, HistoryError> {
+        let mut downloads = Vec::new();
+        for item in self.tree.iter() {
+            let (_, value) = item?;
+            if let Ok(download) = bincode::deserialize::<Download>(&value) {
+                downloads.push(download);
+            }
+        }
+        downloads.sort_by_cached_key(|download| download.song_name.to_lowercase());
+        Ok(downloads)
+    }
+
+    /// Forgets a download. Deleting the file is left to the caller.
+    pub fn remove(&self, song_id: &str) -> Result<(), HistoryError> {
+        self.tree.remove(song_id.as_bytes())?;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
 // Unchanged UserPlaylist and PlaylistManager sections...
 // #[derive(Serialize, Deserialize, Debug, Clone)]
 // struct UserPlaylist {
@@ -290,3 +681,114 @@ impl HistoryDB {
 //         ));
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rows as stored before songs carried a duration and thumbnail, which left them as is
+    const OLD_HISTORY_ENTRY: &[u8] = b"\x04\x00\x00\x00\x00\x00\x00\x00Song\x0b\x00\x00\x00\x00\x00\x00\x00dQw4w9WgXcQ\x01\x00\x00\x00\x00\x00\x00\x00\x06\x00\x00\x00\x00\x00\x00\x00Artist\x00\xf1Se\x00\x00\x00\x00";
+    const OLD_METADATA: &[u8] = b"\x01\xd5\x00\x00\x00\x01\x06\x00\x00\x00\x00\x00\x00\x00Artist\x01\x19\x00\x00\x00\x00\x00\x00\x00https://i.ytimg.com/a.jpg\x00\xf1Se\x00\x00\x00\x00";
+
+    // A history in a fresh directory, removed when the directory handle drops
+    fn open() -> (tempfile::TempDir, HistoryDB) {
+        let dir = tempfile::tempdir().unwrap();
+        let history = HistoryDB::new_at(dir.path()).unwrap();
+        (dir, history)
+    }
+
+    fn entry(song_id: &str, time_stamp: u64) -> HistoryEntry {
+        HistoryEntry {
+            song_name: format!("Song {}", song_id),
+            song_id: song_id.to_string(),
+            artist_name: vec!["Artist".to_string()],
+            time_stamp,
+        }
+    }
+
+    fn ids(history: &HistoryDB) -> Vec<SongId> {
+        history
+            .get_history()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.song_id)
+            .collect()
+    }
+
+    #[test]
+    fn history_lists_the_most_recent_first() {
+        let (_dir, history) = open();
+        history.add_entry(&entry("b", 2)).unwrap();
+        history.add_entry(&entry("a", 3)).unwrap();
+        history.add_entry(&entry("c", 1)).unwrap();
+        assert_eq!(ids(&history), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn replaying_a_song_moves_it_to_the_top() {
+        let (_dir, history) = open();
+        history.add_entry(&entry("a", 1)).unwrap();
+        history.add_entry(&entry("b", 2)).unwrap();
+        history.add_entry(&entry("a", 3)).unwrap();
+        assert_eq!(ids(&history), ["a", "b"]);
+    }
+
+    #[test]
+    fn history_keeps_the_most_recent_entries() {
+        let (_dir, history) = open();
+        // Added newest first, with IDs sorting opposite to their age
+        let total = HISTORY_LIMIT + 5;
+        for i in 0..total {
+            let time_stamp = (total - i) as u64;
+            history
+                .add_entry(&entry(&format!("{:03}", i), time_stamp))
+                .unwrap();
+        }
+        let expected: Vec<SongId> = (0..HISTORY_LIMIT).map(|i| format!("{:03}", i)).collect();
+        assert_eq!(ids(&history), expected);
+    }
+
+    #[test]
+    fn deleting_removes_only_that_entry() {
+        let (_dir, history) = open();
+        history.add_entry(&entry("a", 1)).unwrap();
+        history.add_entry(&entry("b", 2)).unwrap();
+        let generation = history.generation();
+        history.delete_entry("a").unwrap();
+        assert_eq!(ids(&history), ["b"]);
+        assert_ne!(history.generation(), generation);
+        history.delete_entry("missing").unwrap();
+        assert_eq!(ids(&history), ["b"]);
+    }
+
+    #[test]
+    fn clearing_empties_the_history() {
+        let (_dir, history) = open();
+        history.add_entry(&entry("a", 1)).unwrap();
+        history.clear_history().unwrap();
+        assert!(ids(&history).is_empty());
+    }
+
+    #[test]
+    fn old_history_entries_still_read() {
+        let entry: HistoryEntry = bincode::deserialize(OLD_HISTORY_ENTRY).unwrap();
+        assert_eq!(entry.song_name, "Song");
+        assert_eq!(entry.song_id, "dQw4w9WgXcQ");
+        assert_eq!(entry.artist_name, vec!["Artist"]);
+        assert_eq!(entry.time_stamp, 1_700_000_000);
+        assert_eq!(bincode::serialize(&entry).unwrap(), OLD_HISTORY_ENTRY);
+    }
+
+    #[test]
+    fn old_metadata_still_reads() {
+        let metadata: SongMetadata = bincode::deserialize(OLD_METADATA).unwrap();
+        assert_eq!(metadata.duration, Some(213));
+        assert_eq!(metadata.channel.as_deref(), Some("Artist"));
+        assert_eq!(
+            metadata.thumbnail.as_deref(),
+            Some("https://i.ytimg.com/a.jpg")
+        );
+        assert_eq!(metadata.fetched_at, 1_700_000_000);
+        assert_eq!(bincode::serialize(&metadata).unwrap(), OLD_METADATA);
+    }
+}
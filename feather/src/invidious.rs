@@ -0,0 +1,264 @@
+// This file talks to an Invidious instance's public API, used when YouTube blocks RustyPipe
+use crate::database::SongMetadata;
+use crate::proxy;
+use crate::yt::{SearchProvider, SearchResult, YtError};
+use crate::{ArtistName, ChannelName, PlaylistId, PlaylistName, SongId, SongName, SongUrl};
+use reqwest::StatusCode;
+use reqwest::header::RETRY_AFTER;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Video {
+    title: String,
+    video_id: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    length_seconds: Option<u32>,
+    #[serde(default)]
+    video_thumbnails: Vec<Thumbnail>,
+}
+
+// Search results mix videos, channels and playlists
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SearchItem {
+    Video(Video),
+    #[serde(rename_all = "camelCase")]
+    Playlist {
+        title: String,
+        playlist_id: String,
+        #[serde(default)]
+        author: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct Suggestions {
+    suggestions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Format {
+    url: String,
+    #[serde(rename = "type")]
+    mime: String,
+    #[serde(default)]
+    bitrate: String, // Invidious sends it as a string
+}
+
+#[derive(Deserialize)]
+struct Thumbnail {
+    url: String,
+    #[serde(default)]
+    width: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoDetails {
+    #[serde(default)]
+    length_seconds: Option<u32>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    video_thumbnails: Vec<Thumbnail>,
+    #[serde(default)]
+    adaptive_formats: Vec<Format>,
+    #[serde(default)]
+    recommended_videos: Vec<Video>,
+}
+
+#[derive(Deserialize)]
+struct Playlist {
+    videos: Vec<Video>,
+}
+
+/// Fetches songs through an Invidious instance, e.g. `https://yewtu.be`.
+pub struct InvidiousProvider {
+    http: reqwest::Client,
+    instance: String, // Base URL without a trailing slash
+}
+
+impl InvidiousProvider {
+    /// Creates a provider for the instance at the given base URL.
+    pub fn new(instance: &str) -> Self {
+        let http = proxy::client_builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        InvidiousProvider {
+            http,
+            instance: instance.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, YtError> {
+        let response = self
+            .http
+            .get(format!("{}/api/v1/{}", self.instance, path))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| YtError::Network(e.to_string()))?;
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => {
+                // Only the seconds form is read; an HTTP date falls back to our own backoff
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|seconds| seconds.trim().parse().ok())
+                    .map(Duration::from_secs);
+                return Err(YtError::RateLimited(retry_after));
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(YtError::Unavailable("not found on Invidious".to_string()));
+            }
+            status if !status.is_success() => {
+                return Err(YtError::Network(format!("Invidious answered {}", status)));
+            }
+            _ => (),
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| YtError::Parse(e.to_string()))
+    }
+}
+
+// URL of the widest thumbnail
+fn largest(thumbnails: Vec<Thumbnail>) -> Option<String> {
+    thumbnails
+        .into_iter()
+        .max_by_key(|thumbnail| thumbnail.width)
+        .map(|thumbnail| thumbnail.url)
+}
+
+// Invidious only knows the uploader, which stands in for the artist
+fn artists(author: String) -> Vec<ArtistName> {
+    if author.is_empty() {
+        Vec::new()
+    } else {
+        vec![author]
+    }
+}
+
+fn song_map(videos: Vec<Video>) -> HashMap<(SongName, SongId), Vec<ArtistName>> {
+    videos
+        .into_iter()
+        .map(|video| ((video.title, video.video_id), artists(video.author)))
+        .collect()
+}
+
+impl SearchProvider for InvidiousProvider {
+    fn name(&self) -> &'static str {
+        "Invidious"
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SearchResult>, YtError> {
+        let items: Vec<SearchItem> = self
+            .get("search", &[("q", query), ("type", "video")])
+            .await?;
+        Ok(items
+            .into_iter()
+            .filter_map(|item| match item {
+                SearchItem::Video(video) => Some(SearchResult {
+                    metadata: SongMetadata {
+                        duration: video.length_seconds,
+                        channel: Some(video.author.clone()).filter(|author| !author.is_empty()),
+                        thumbnail: largest(video.video_thumbnails),
+                        fetched_at: 0, // Stamped by the cache
+                    },
+                    song_name: video.title,
+                    song_id: video.video_id,
+                    artist_name: artists(video.author),
+                }),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn suggestions(&self, prefix: &str) -> Result<Vec<String>, YtError> {
+        self.get::<Suggestions>("search/suggestions", &[("q", prefix)])
+            .await
+            .map(|response| response.suggestions)
+    }
+
+    async fn stream(&self, id: &SongId) -> Result<(SongUrl, &'static str), YtError> {
+        let details: VideoDetails = self.get(&format!("videos/{}", id), &[]).await?;
+        let format = details
+            .adaptive_formats
+            .into_iter()
+            .filter(|format| format.mime.starts_with("audio/"))
+            .max_by_key(|format| format.bitrate.parse::<u64>().unwrap_or(0))
+            .ok_or_else(|| YtError::Unavailable("no audio stream".to_string()))?;
+        let extension = if format.mime.starts_with("audio/mp4") {
+            "m4a"
+        } else {
+            "webm"
+        };
+        Ok((format.url, extension))
+    }
+
+    async fn fetch_playlist(
+        &self,
+        search_query: &str,
+    ) -> Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, YtError> {
+        let items: Vec<SearchItem> = self
+            .get("search", &[("q", search_query), ("type", "playlist")])
+            .await?;
+        Ok(items
+            .into_iter()
+            .filter_map(|item| match item {
+                SearchItem::Playlist {
+                    title,
+                    playlist_id,
+                    author,
+                } => Some((title, (playlist_id, artists(author)))),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn fetch_playlist_songs(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        self.get::<Playlist>(&format!("playlists/{}", playlist_id), &[])
+            .await
+            .map(|playlist| song_map(playlist.videos))
+    }
+
+    async fn metadata(&self, id: &SongId) -> Result<SongMetadata, YtError> {
+        let details: VideoDetails = self.get(&format!("videos/{}", id), &[]).await?;
+        Ok(SongMetadata {
+            duration: details.length_seconds,
+            channel: details.author,
+            thumbnail: largest(details.video_thumbnails),
+            fetched_at: 0, // Stamped by the cache
+        })
+    }
+
+    async fn fetch_related_song(
+        &self,
+        song_id: SongId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        self.get::<VideoDetails>(&format!("videos/{}", song_id), &[])
+            .await
+            .map(|details| song_map(details.recommended_videos))
+    }
+}
@@ -1,5 +1,19 @@
+// Guards of std mutexes are only held in short synchronous sections and values are
+// cloned out before awaiting, so a task never blocks a worker thread on a lock
+#![deny(clippy::await_holding_lock)]
+
+pub mod archive;
+pub mod backend;
+pub mod cookies;
 pub mod database;
+pub mod invidious;
+pub mod lyrics;
+pub mod mpv_ipc;
+pub mod paths;
 pub mod player;
+pub mod proxy;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 pub mod yt;
 
 /// Input/Return Types
@@ -0,0 +1,98 @@
+// This file looks up song lyrics on LRCLIB
+use crate::backend::Song;
+use crate::database::Lyrics;
+use crate::proxy;
+use serde::Deserialize;
+use std::time::Duration;
+
+const SEARCH_URL: &str = "https://lrclib.net/api/search";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const DURATION_SLACK: f64 = 5.0; // Seconds a match may differ from the song's length
+
+// One LRCLIB search result
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Record {
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    plain_lyrics: Option<String>,
+    #[serde(default)]
+    synced_lyrics: Option<String>,
+}
+
+/// Looks up the lyrics of a song on LRCLIB, preferring synced ones of the right length.
+pub async fn fetch(song: &Song, duration: Option<u32>) -> Result<Lyrics, String> {
+    let http = proxy::client_builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("Feather/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let artist = song.artist_name.first().map(String::as_str).unwrap_or("");
+    let mut records = search(
+        &http,
+        &[("track_name", &song.song_name), ("artist_name", artist)],
+    )
+    .await?;
+    if records.is_empty() {
+        // YouTube titles often carry the artist or extras the track name lacks
+        records = search(&http, &[("q", &song.song_name)]).await?;
+    }
+
+    let fits = |record: &Record| match (duration, record.duration) {
+        (Some(expected), Some(actual)) => (expected as f64 - actual).abs() <= DURATION_SLACK,
+        _ => true,
+    };
+    let synced = records
+        .iter()
+        .filter(|record| fits(record))
+        .find_map(|record| record.synced_lyrics.as_deref().map(parse_lrc))
+        .filter(|lines| !lines.is_empty());
+    if let Some(lines) = synced {
+        return Ok(Lyrics::Synced(lines));
+    }
+    let plain = records
+        .into_iter()
+        .filter_map(|record| record.plain_lyrics)
+        .find(|text| !text.trim().is_empty());
+    Ok(plain.map_or(Lyrics::Missing, Lyrics::Plain))
+}
+
+async fn search(http: &reqwest::Client, query: &[(&str, &str)]) -> Result<Vec<Record>, String> {
+    http.get(SEARCH_URL)
+        .query(query)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Parses LRC lines such as "[01:02.50]text", where one line may carry several timestamps.
+// Tags such as "[ar:Artist]" are skipped.
+fn parse_lrc(text: &str) -> Vec<(f64, String)> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let mut rest = line.trim();
+        let mut times = Vec::new();
+        while let Some((tag, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let Some(time) = parse_timestamp(tag) else {
+                break;
+            };
+            times.push(time);
+            rest = after;
+        }
+        for time in times {
+            lines.push((time, rest.trim().to_string()));
+        }
+    }
+    lines.sort_by(|a, b| a.0.total_cmp(&b.0));
+    lines
+}
+
+fn parse_timestamp(tag: &str) -> Option<f64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    Some(minutes.parse::<u32>().ok()? as f64 * 60.0 + seconds.parse::<f64>().ok()?)
+}
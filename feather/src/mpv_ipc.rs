@@ -0,0 +1,258 @@
+// This file drives an mpv executable over its JSON IPC, for builds without libmpv
+use crate::player::{MpvBackend, MpvError};
+use serde_json::{Value, json};
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long mpv gets to open its IPC socket after starting.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a reply may take before the request fails.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = std::fs::File; // Named pipes open like files
+
+/// A reply read by the I/O thread, with the ID of the request it answers.
+/// Errors are the I/O failures that ended the connection.
+type Reply = (u64, Result<Value, String>);
+
+/// The connection to mpv. Requests and their replies go one at a time through a
+/// dedicated I/O thread, so a request can give up waiting on every platform,
+/// including Windows, whose named pipes have no read timeout.
+struct Connection {
+    requests: Sender<(u64, String)>, // Lines for the I/O thread, with their request ID
+    replies: Receiver<Reply>,        // What the I/O thread read back
+    next_id: u64,
+    stalled: Option<u64>, // Request that timed out and that mpv still owes a reply to
+}
+
+/// An `mpv` process started with `--input-ipc-server`, stopped when dropped.
+pub struct MpvIpc {
+    child: Child,
+    #[cfg(unix)]
+    socket: PathBuf, // Removed once mpv is stopped
+    connection: Mutex<Connection>,
+}
+
+impl MpvIpc {
+    /// Starts `mpv`, or the executable named by `FEATHER_MPV_PATH`, and connects to it.
+    pub fn spawn() -> Result<Self, MpvError> {
+        let socket = socket_path();
+        let executable = env::var("FEATHER_MPV_PATH").unwrap_or_else(|_| "mpv".to_string());
+        let mut child = Command::new(&executable)
+            .arg("--idle=yes")
+            .arg("--no-terminal")
+            .arg("--video=no")
+            .arg(format!("--input-ipc-server={}", socket.display()))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| MpvError::Ipc(format!("cannot start {}: {}", executable, e)))?;
+
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        let stream = loop {
+            match connect(&socket) {
+                Ok(stream) => break stream,
+                Err(e) => {
+                    if let Ok(Some(status)) = child.try_wait() {
+                        return Err(MpvError::Ipc(format!("mpv exited with {}", status)));
+                    }
+                    if Instant::now() >= deadline {
+                        let _ = child.kill();
+                        return Err(MpvError::Ipc(format!("cannot connect to mpv: {}", e)));
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+            }
+        };
+        let writer = stream
+            .try_clone()
+            .map_err(|e| MpvError::Ipc(e.to_string()))?;
+        let (requests, lines) = mpsc::channel();
+        let (replies_tx, replies) = mpsc::channel();
+        thread::spawn(move || serve(BufReader::new(stream), writer, lines, replies_tx));
+        Ok(MpvIpc {
+            child,
+            #[cfg(unix)]
+            socket,
+            connection: Mutex::new(Connection {
+                requests,
+                replies,
+                next_id: 0,
+                stalled: None,
+            }),
+        })
+    }
+
+    /// Sends a command and waits up to `REPLY_TIMEOUT` for its reply. While mpv still
+    /// owes the reply to a request that timed out, requests fail right away instead of
+    /// queueing behind it, so a hung mpv cannot freeze the caller.
+    fn request(&self, command: &[&str]) -> Result<Value, MpvError> {
+        let mut connection = self
+            .connection
+            .lock()
+            .map_err(|_| MpvError::Ipc("connection lock poisoned".to_string()))?;
+        if let Some(stalled) = connection.stalled {
+            while let Ok((id, _)) = connection.replies.try_recv() {
+                if id >= stalled {
+                    connection.stalled = None;
+                }
+            }
+            if connection.stalled.is_some() {
+                return Err(MpvError::Ipc("mpv is not answering".to_string()));
+            }
+        }
+        connection.next_id += 1;
+        let id = connection.next_id;
+        let mut line = json!({ "command": command, "request_id": id }).to_string();
+        line.push('\n');
+        connection
+            .requests
+            .send((id, line))
+            .map_err(|_| MpvError::Ipc("mpv closed the connection".to_string()))?;
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        let reply = loop {
+            match connection
+                .replies
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok((reply_id, reply)) if reply_id == id => break reply,
+                Ok(_) => continue, // Late reply to a request that timed out
+                Err(RecvTimeoutError::Timeout) => {
+                    connection.stalled = Some(id);
+                    return Err(MpvError::Ipc(format!(
+                        "no reply from mpv within {} seconds",
+                        REPLY_TIMEOUT.as_secs()
+                    )));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(MpvError::Ipc("mpv closed the connection".to_string()));
+                }
+            }
+        };
+        let reply = reply.map_err(MpvError::Ipc)?;
+        match reply.get("error").and_then(Value::as_str) {
+            Some("success") => Ok(reply.get("data").cloned().unwrap_or(Value::Null)),
+            Some(error) => Err(MpvError::CommandError(format!(
+                "{}: {}",
+                command.join(" "),
+                error
+            ))),
+            None => Err(MpvError::Ipc("reply without a status".to_string())),
+        }
+    }
+
+    fn get_property(&self, name: &str) -> Result<Value, MpvError> {
+        self.request(&["get_property", name])
+            .map_err(|e| MpvError::PropertyError(format!("{}: {}", name, e)))
+    }
+}
+
+impl MpvBackend for MpvIpc {
+    fn command(&self, name: &str, args: &[&str]) -> Result<(), MpvError> {
+        let mut command = vec![name];
+        command.extend_from_slice(args);
+        self.request(&command).map(|_| ())
+    }
+
+    fn set_property(&self, name: &str, value: &str) -> Result<(), MpvError> {
+        // `set` parses the value like mpv.conf does, whatever the property's type
+        self.request(&["set", name, value]).map(|_| ())
+    }
+
+    fn get_f64(&self, name: &str) -> Result<f64, MpvError> {
+        self.get_property(name)?
+            .as_f64()
+            .ok_or_else(|| MpvError::PropertyError(format!("{} is not a number", name)))
+    }
+
+    fn get_bool(&self, name: &str) -> Result<bool, MpvError> {
+        self.get_property(name)?
+            .as_bool()
+            .ok_or_else(|| MpvError::PropertyError(format!("{} is not a flag", name)))
+    }
+}
+
+impl Drop for MpvIpc {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+/// Runs on the I/O thread: writes each request and reads until its reply, skipping
+/// events. Stops once the connection fails or `MpvIpc` is dropped.
+fn serve(
+    mut reader: BufReader<Stream>,
+    mut writer: Stream,
+    lines: Receiver<(u64, String)>,
+    replies: Sender<Reply>,
+) {
+    for (id, line) in lines {
+        let reply = exchange(&mut reader, &mut writer, id, &line);
+        let failed = reply.is_err();
+        if replies.send((id, reply)).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// Sends one request line and returns mpv's reply to it.
+fn exchange(
+    reader: &mut BufReader<Stream>,
+    writer: &mut Stream,
+    id: u64,
+    line: &str,
+) -> Result<Value, String> {
+    writer
+        .write_all(line.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| e.to_string())?;
+    loop {
+        let mut reply = String::new();
+        if reader.read_line(&mut reply).map_err(|e| e.to_string())? == 0 {
+            return Err("mpv closed the connection".to_string());
+        }
+        let Ok(reply) = serde_json::from_str::<Value>(&reply) else {
+            continue;
+        };
+        if reply.get("request_id").and_then(Value::as_u64) == Some(id) {
+            return Ok(reply);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn socket_path() -> PathBuf {
+    env::temp_dir().join(format!("feather-mpv-{}.sock", std::process::id()))
+}
+
+#[cfg(windows)]
+fn socket_path() -> PathBuf {
+    PathBuf::from(format!(r"\\.\pipe\feather-mpv-{}", std::process::id()))
+}
+
+#[cfg(unix)]
+fn connect(socket: &Path) -> std::io::Result<Stream> {
+    Stream::connect(socket)
+}
+
+#[cfg(windows)]
+fn connect(socket: &Path) -> std::io::Result<Stream> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(socket)
+}
@@ -0,0 +1,24 @@
+// This file resolves where Feather keeps its on-disk state
+use std::path::PathBuf;
+
+/// Locations of Feather's on-disk state, created once at startup and passed down.
+#[derive(Clone, Debug)]
+pub struct Paths {
+    pub data_dir: PathBuf, // Base directory for databases and caches
+}
+
+impl Paths {
+    /// Uses the given directory as the data directory.
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self { data_dir }
+    }
+}
+
+impl Default for Paths {
+    /// Defaults to `<data dir>/Feather`, or `/tmp/Feather` when the platform has no data dir.
+    fn default() -> Self {
+        let mut data_dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
+        data_dir.push("Feather");
+        Self { data_dir }
+    }
+}
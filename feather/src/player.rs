@@ -1,17 +1,32 @@
+#[cfg(feature = "libmpv")]
 use libmpv2::Mpv; // We are not using libmpv library because it was requiring user to install an old version which was not available in many distros so we decided to opt for libmpv2 which is a fork of it
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, Weak};
 
 /// The `Player` struct represents a media player using the MPV library.
 /// It provides functionalities to control playback, retrieve metadata,
 /// and manage audio optimizations.
 pub struct Player {
-    /// An instance of the MPV player wrapped in an `Arc` for thread safety.
-    pub player: Arc<Mpv>,
+    /// The mpv instance, linked in or driven over IPC.
+    mpv: Arc<dyn MpvBackend>,
+}
+
+/// A handle to a `Player` that does not keep mpv running, for tasks that may outlive it.
+pub struct WeakPlayer {
+    mpv: Weak<dyn MpvBackend>,
+}
+
+impl WeakPlayer {
+    /// Returns the player, unless it was dropped.
+    pub fn upgrade(&self) -> Option<Player> {
+        self.mpv.upgrade().map(|mpv| Player { mpv })
+    }
 }
 
 /// Enum representing possible errors when interacting with the MPV player.
 #[derive(Debug, thiserror::Error)]
 pub enum MpvError {
+    #[cfg(feature = "libmpv")]
     #[error("Mpv error: {0}")]
     Mpv(#[from] libmpv2::Error),
     #[error("Failed to initialize MPV")]
@@ -22,17 +37,73 @@ pub enum MpvError {
     LoadFileError(String),
     #[error("Property retrieval failed: {0}")]
     PropertyError(String),
+    #[error("Mpv IPC error: {0}")]
+    Ipc(String),
     #[error("Unknown error: {0}")]
     Other(String),
 }
 
+/// The few mpv calls `Player` is built on, so it can drive libmpv or an mpv process.
+pub trait MpvBackend: Send + Sync {
+    /// Runs an mpv command, such as `loadfile` or `seek`, with string arguments.
+    fn command(&self, name: &str, args: &[&str]) -> Result<(), MpvError>;
+    /// Sets a property from its string form, as it would be written in mpv.conf.
+    fn set_property(&self, name: &str, value: &str) -> Result<(), MpvError>;
+    /// Reads a numeric property.
+    fn get_f64(&self, name: &str) -> Result<f64, MpvError>;
+    /// Reads a flag property.
+    fn get_bool(&self, name: &str) -> Result<bool, MpvError>;
+}
+
+#[cfg(feature = "libmpv")]
+impl MpvBackend for Mpv {
+    fn command(&self, name: &str, args: &[&str]) -> Result<(), MpvError> {
+        Mpv::command(self, name, args)?;
+        Ok(())
+    }
+
+    fn set_property(&self, name: &str, value: &str) -> Result<(), MpvError> {
+        Mpv::set_property(self, name, value)?;
+        Ok(())
+    }
+
+    fn get_f64(&self, name: &str) -> Result<f64, MpvError> {
+        Ok(Mpv::get_property(self, name)?)
+    }
+
+    fn get_bool(&self, name: &str) -> Result<bool, MpvError> {
+        Ok(Mpv::get_property(self, name)?)
+    }
+}
+
+/// Starts libmpv, or an mpv process when `FEATHER_PLAYER=ipc` is set.
+#[cfg(feature = "libmpv")]
+fn open_backend() -> Result<Arc<dyn MpvBackend>, MpvError> {
+    if std::env::var("FEATHER_PLAYER").as_deref() == Ok("ipc") {
+        return Ok(Arc::new(crate::mpv_ipc::MpvIpc::spawn()?));
+    }
+    Ok(Arc::new(Mpv::new()?))
+}
+
+/// Starts an mpv process, as builds without libmpv have no other player.
+#[cfg(not(feature = "libmpv"))]
+fn open_backend() -> Result<Arc<dyn MpvBackend>, MpvError> {
+    Ok(Arc::new(crate::mpv_ipc::MpvIpc::spawn()?))
+}
+
 impl Player {
     /// Creates a new `Player` instance and configures MPV settings for optimized audio playback.
-    pub fn new(cookies: Option<String>) -> Result<Self, MpvError> {
-        let mpv = Mpv::new()?;
-        if cookies.is_some() {
+    /// `cookies` is a Netscape-format cookies file sent with YouTube requests,
+    /// `proxy` an HTTP proxy URL streams are fetched through.
+    pub fn new(cookies: Option<&Path>, proxy: Option<&str>) -> Result<Self, MpvError> {
+        let mpv = open_backend()?;
+        if let Some(proxy) = proxy {
+            mpv.set_property("http-proxy", proxy)?;
+        }
+        if let Some(cookies) = cookies {
             // setting cookies  if given by user
-            mpv.set_property("cookies-file", cookies.unwrap())?;
+            mpv.set_property("cookies", "yes")?;
+            mpv.set_property("cookies-file", cookies.to_string_lossy().as_ref())?;
         }
 
         // Disable video to save memory
@@ -52,75 +123,153 @@ impl Player {
         )?;
 
         // Audio optimization
-        mpv.set_property("audio-buffer", 0.1)?; // 100ms audio buffer
+        mpv.set_property("audio-buffer", "0.1")?; // 100ms audio buffer
         mpv.set_property("audio-channels", "stereo")?; // Force stereo audio
 
-        let mpv = Arc::new(mpv);
-        Ok(Self { player: mpv })
+        Ok(Self { mpv })
+    }
+
+    /// Wraps an mpv that is already set up, such as `testing::FakeMpv`.
+    pub fn with_backend(mpv: Arc<dyn MpvBackend>) -> Self {
+        Self { mpv }
+    }
+
+    /// Returns a handle that does not keep mpv running.
+    pub fn downgrade(&self) -> WeakPlayer {
+        WeakPlayer {
+            mpv: Arc::downgrade(&self.mpv),
+        }
+    }
+
+    /// Stops mpv for good; every later call fails.
+    pub fn quit(&self) -> Result<(), MpvError> {
+        self.mpv.command("quit", &[])
     }
 
     /// Loads and plays a media file from a given URL.
     pub fn play(&self, url: &str) -> Result<(), MpvError> {
-         if let Ok(true) = self.player.get_property("pause") {
+        if let Ok(true) = self.mpv.get_bool("pause") {
             self.unpause()?;
-        } // Quick fix will improve 
-        self.player.command("loadfile", &[url])?; // Replace the current playback
+        } // Quick fix will improve
+        self.mpv.command("loadfile", &[url])?; // Replace the current playback
+        Ok(())
+    }
+
+    /// Points mpv at a cookies file, used for the next song loaded.
+    pub fn set_cookies_file(&self, cookies: &Path) -> Result<(), MpvError> {
+        self.mpv.set_property("cookies", "yes")?;
+        self.mpv
+            .set_property("cookies-file", cookies.to_string_lossy().as_ref())?;
         Ok(())
     }
 
     /// Pauses playback.
     pub fn pause(&self) -> Result<(), MpvError> {
-        self.player.command("set", &["pause", "yes"])?;
+        self.mpv.command("set", &["pause", "yes"])?;
         Ok(())
     }
 
     /// Resumes playback.
     pub fn unpause(&self) -> Result<(), MpvError> {
-        self.player.command("set", &["pause", "no"])?;
+        self.mpv.command("set", &["pause", "no"])?;
         Ok(())
     }
 
     /// Toggles between play and pause states.
     pub fn play_pause(&self) -> Result<(), MpvError> {
-        match self.player.get_property::<bool>("pause") {
-            Ok(true) => self.unpause()?,
-            Ok(false) => self.pause()?,
-            Err(_) => todo!(),
+        if self.mpv.get_bool("pause")? {
+            self.unpause()
+        } else {
+            self.pause()
         }
-        Ok(())
     }
 
     /// Seeks forward by 5 seconds in the current track.
     pub fn seek_forward(&self) -> Result<(), MpvError> {
-        self.player.command("seek", &["5", "relative"])?;
+        self.mpv.command("seek", &["5", "relative"])?;
         Ok(())
     }
 
     /// Seeks backward by 5 seconds in the current track.
     pub fn seek_backword(&self) -> Result<(), MpvError> {
-        self.player.command("seek", &["-5", "relative"])?;
+        self.mpv.command("seek", &["-5", "relative"])?;
+        Ok(())
+    }
+
+    /// Seeks by the given number of seconds, backwards when negative.
+    pub fn seek_by(&self, seconds: f64) -> Result<(), MpvError> {
+        self.mpv
+            .command("seek", &[&seconds.to_string(), "relative"])?;
+        Ok(())
+    }
+
+    /// Seeks to the given position in seconds from the start of the current track.
+    pub fn seek_to(&self, seconds: f64) -> Result<(), MpvError> {
+        self.mpv
+            .command("seek", &[&seconds.max(0.0).to_string(), "absolute"])?;
+        Ok(())
+    }
+
+    /// Sets the playback volume, clamped to the range 0-100.
+    pub fn set_volume(&self, volume: u8) -> Result<(), MpvError> {
+        self.mpv
+            .set_property("volume", &volume.min(100).to_string())?;
+        Ok(())
+    }
+
+    /// Returns the playback volume (0-100).
+    pub fn volume(&self) -> Result<u8, MpvError> {
+        let volume = self.mpv.get_f64("volume")?;
+        Ok(volume.round().clamp(0.0, 100.0) as u8)
+    }
+
+    /// Seeks to the given position as a percentage (0-100) of the current track.
+    pub fn seek_percent(&self, percent: f64) -> Result<(), MpvError> {
+        let percent = percent.clamp(0.0, 100.0).to_string();
+        self.mpv.command("seek", &[&percent, "absolute-percent"])?;
         Ok(())
     }
 
+    /// Enables or disables looping the current track.
+    pub fn set_loop(&self, enabled: bool) -> Result<(), MpvError> {
+        self.mpv
+            .set_property("loop", if enabled { "inf" } else { "no" })?;
+        Ok(())
+    }
+
+    /// Returns whether mpv has nothing loaded, before the first song or after one ended.
+    pub fn is_idle(&self) -> bool {
+        self.mpv.get_bool("idle-active").unwrap_or(true)
+    }
+
+    /// Returns the playback position in seconds.
+    pub fn position(&self) -> Result<f64, MpvError> {
+        self.mpv.get_f64("time-pos")
+    }
+
     /// Retrieves the current playback time as a string.
     pub fn get_current_time(&self) -> String {
-        self.player
-            .get_property("time-pos")
-            .unwrap_or(0.0)
-            .to_string()
+        self.position_secs().to_string()
     }
 
     /// Retrieves the duration of the currently playing media.
     pub fn duration(&self) -> String {
-        self.player
-            .get_property("duration")
-            .unwrap_or(0.0)
-            .to_string()
+        self.duration_secs().to_string()
+    }
+
+    /// Returns the playback position in seconds, or zero when nothing is loaded.
+    pub fn position_secs(&self) -> f64 {
+        self.position().unwrap_or(0.0)
+    }
+
+    /// Returns the duration of the current media in seconds, or zero while it is unknown.
+    pub fn duration_secs(&self) -> f64 {
+        self.mpv.get_f64("duration").unwrap_or(0.0)
     }
 
     /// Returns whether a media file is currently playing.
     pub fn is_playing(&self) -> Result<bool, MpvError> {
-        let pause: bool = self.player.get_property("pause")?;
+        let pause = self.mpv.get_bool("pause")?;
         Ok(!pause)
     }
 }
@@ -0,0 +1,86 @@
+// This file resolves the proxy network traffic is sent through
+use std::env;
+
+/// Variables read for the proxy, most specific first.
+const VARIABLES: &[&str] = &[
+    "FEATHER_PROXY",
+    "ALL_PROXY",
+    "all_proxy",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+];
+
+/// Enum representing possible errors in the proxy setting.
+#[derive(Debug, thiserror::Error)]
+pub enum ProxyError {
+    #[error("Invalid proxy URL in {0}: {1}")]
+    InvalidUrl(&'static str, String),
+    #[error("Unsupported proxy scheme \"{1}\" in {0}, use http, https, socks5 or socks5h")]
+    UnsupportedScheme(&'static str, String),
+}
+
+/// A validated proxy URL.
+#[derive(Clone, Debug)]
+pub struct Proxy {
+    url: String,
+}
+
+impl Proxy {
+    /// Reads the proxy from `FEATHER_PROXY`, falling back to the usual
+    /// `ALL_PROXY`, `HTTPS_PROXY` and `HTTP_PROXY` variables.
+    pub fn from_env() -> Result<Option<Self>, ProxyError> {
+        for &variable in VARIABLES {
+            if let Some(url) = env::var(variable).ok().filter(|url| !url.trim().is_empty()) {
+                return Self::parse(variable, url.trim()).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse(variable: &'static str, url: &str) -> Result<Self, ProxyError> {
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| ProxyError::InvalidUrl(variable, format!("{} ({})", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(ProxyError::UnsupportedScheme(
+                variable,
+                parsed.scheme().to_string(),
+            ));
+        }
+        if parsed.host_str().is_none() {
+            return Err(ProxyError::InvalidUrl(
+                variable,
+                format!("{} (no host)", url),
+            ));
+        }
+        Ok(Proxy {
+            url: url.to_string(),
+        })
+    }
+
+    /// Returns the proxy URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns whether mpv can use the proxy; it only speaks HTTP proxies.
+    pub fn is_http(&self) -> bool {
+        self.url.starts_with("http://")
+    }
+}
+
+/// Returns a builder for HTTP clients that send their requests through the proxy read by
+/// `Proxy::from_env`. An invalid setting stops Feather at startup, so here it only means
+/// the client connects directly.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match Proxy::from_env()
+        .ok()
+        .flatten()
+        .and_then(|proxy| reqwest::Proxy::all(proxy.url()).ok())
+    {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    }
+}
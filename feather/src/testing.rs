@@ -0,0 +1,78 @@
+// This file holds stand-ins for tests of Feather and its frontends, so they run without mpv
+use crate::backend::Backend;
+use crate::database::HistoryDB;
+use crate::paths::Paths;
+use crate::player::{MpvBackend, MpvError, Player};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// Stands in for mpv, remembering what it was asked to load and playing nothing.
+#[derive(Default)]
+pub struct FakeMpv {
+    loaded: Mutex<Vec<String>>, // Arguments of every `loadfile`, in order
+}
+
+impl FakeMpv {
+    /// Returns everything loaded so far.
+    pub fn loaded(&self) -> Vec<String> {
+        self.loaded
+            .lock()
+            .map(|loaded| loaded.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl MpvBackend for FakeMpv {
+    fn command(&self, name: &str, args: &[&str]) -> Result<(), MpvError> {
+        if name == "loadfile"
+            && let (Ok(mut loaded), Some(url)) = (self.loaded.lock(), args.first())
+        {
+            loaded.push(url.to_string());
+        }
+        Ok(())
+    }
+
+    fn set_property(&self, _name: &str, _value: &str) -> Result<(), MpvError> {
+        Ok(())
+    }
+
+    fn get_f64(&self, _name: &str) -> Result<f64, MpvError> {
+        Ok(0.0)
+    }
+
+    fn get_bool(&self, name: &str) -> Result<bool, MpvError> {
+        Ok(name == "idle-active")
+    }
+}
+
+/// A backend on a fresh data directory, playing into a `FakeMpv`.
+/// Fields drop in order, so the database is closed before its directory goes.
+pub struct TestBackend {
+    pub backend: Arc<Backend>,
+    pub mpv: Arc<FakeMpv>,
+    pub dir: TempDir,
+}
+
+impl TestBackend {
+    /// Creates the backend in a new temporary directory.
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("cannot create a temporary directory");
+        let paths = Paths::new(dir.path().to_path_buf());
+        let history = HistoryDB::new_at(&paths.data_dir).expect("cannot open the history");
+        let mpv = Arc::new(FakeMpv::default());
+        let player = Player::with_backend(mpv.clone());
+        let backend = Backend::with_player(player, Arc::new(history), &paths, None)
+            .expect("cannot create the backend");
+        TestBackend {
+            backend: Arc::new(backend),
+            mpv,
+            dir,
+        }
+    }
+}
+
+impl Default for TestBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
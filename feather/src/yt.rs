@@ -1,72 +1,537 @@
+use crate::cookies::CookieJar;
+use crate::database::{MetadataCache, SongMetadata};
+use crate::invidious::InvidiousProvider;
+use crate::paths::Paths;
+use crate::proxy;
 use crate::{ArtistName, ChannelName, PlaylistId, PlaylistName, SongId, SongName, SongUrl};
-use std::path::PathBuf;
 use rustypipe::{
     client::{RustyPipe, RustyPipeQuery},
-    model::MusicItem,
+    model::{AudioFormat, MusicItem, TrackItem},
     param::StreamFilter,
 };
 use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
 
-/// A client for interacting with YouTube music using RustyPipe.
+/// Consecutive failures after which requests move to the other provider.
+const FAILOVER_AFTER: u32 = 3;
+/// Attempts `with_retry` makes before giving up.
+const RETRY_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for each one after it.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest rate limit waited out; longer ones are reported instead.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Enum representing the ways a YouTube request can fail.
+/// The messages are meant for the status bar.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum YtError {
+    #[error("Network error, check your connection ({0})")]
+    Network(String),
+    #[error("YouTube is limiting requests, try again in a while")]
+    RateLimited(Option<Duration>), // How long the server asked to wait, if it said so in seconds
+    #[error("Unexpected answer from YouTube ({0})")]
+    Parse(String),
+    #[error("Not available: {0}")]
+    Unavailable(String),
+}
+
+impl YtError {
+    /// Returns whether the same request may succeed when tried again.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, YtError::Network(_) | YtError::RateLimited(_))
+    }
+}
+
+impl From<rustypipe::error::Error> for YtError {
+    fn from(e: rustypipe::error::Error) -> Self {
+        use rustypipe::error::{Error, ExtractionError};
+        match &e {
+            Error::HttpStatus(429, _) => YtError::RateLimited(None),
+            Error::Extraction(
+                ExtractionError::NotFound { .. } | ExtractionError::Unavailable { .. },
+            ) => YtError::Unavailable(e.to_string()),
+            Error::Extraction(_) => YtError::Parse(e.to_string()),
+            _ => YtError::Network(e.to_string()),
+        }
+    }
+}
+
+/// Runs a request up to `RETRY_ATTEMPTS` times while it fails with a transient error.
+/// Waits grow exponentially with random jitter, or follow the server's retry-after.
+pub async fn with_retry<T, F, Fut>(mut request: F) -> Result<T, YtError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, YtError>>,
+{
+    let mut attempt = 0;
+    loop {
+        let delay = match request().await {
+            Err(e) if e.is_transient() && attempt + 1 < RETRY_ATTEMPTS => match e {
+                YtError::RateLimited(Some(wait)) if wait > MAX_RETRY_AFTER => return Err(e),
+                YtError::RateLimited(Some(wait)) => wait,
+                _ => backoff(attempt),
+            },
+            result => return result,
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Wait before retry number `attempt`, with up to half of it added as jitter
+/// so clients failing together do not retry together.
+fn backoff(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt);
+    let random = RandomState::new().build_hasher().finish();
+    delay + delay.mul_f64((random % 1000) as f64 / 2000.0)
+}
+
+/// A song found by a search, with the details the search listed along with it.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub song_name: SongName,
+    pub song_id: SongId,
+    pub artist_name: Vec<ArtistName>,
+    pub metadata: SongMetadata, // Duration and thumbnail, where the search had them
+}
+
+/// A source of YouTube search results and audio streams.
+/// Errors are human readable messages, ready to be shown to the user.
+pub trait SearchProvider {
+    /// Short name shown when switching providers.
+    fn name(&self) -> &'static str;
+
+    /// Searches for songs, returning them in ranking order.
+    fn search(
+        &self,
+        query: &str,
+    ) -> impl Future<Output = Result<Vec<SearchResult>, YtError>> + Send;
+
+    /// Fetches search suggestions for a partially typed query.
+    fn suggestions(
+        &self,
+        prefix: &str,
+    ) -> impl Future<Output = Result<Vec<String>, YtError>> + Send;
+
+    /// Resolves the audio stream of a song, with the file extension of its container.
+    fn stream(
+        &self,
+        id: &SongId,
+    ) -> impl Future<Output = Result<(SongUrl, &'static str), YtError>> + Send;
+
+    /// Searches for playlists, keyed by name, with their ID and channel names.
+    fn fetch_playlist(
+        &self,
+        search_query: &str,
+    ) -> impl Future<Output = Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, YtError>>
+    + Send;
+
+    /// Fetches the songs of a playlist.
+    fn fetch_playlist_songs(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> impl Future<Output = Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError>> + Send;
+
+    /// Fetches the duration, channel and thumbnail of a song.
+    fn metadata(&self, id: &SongId) -> impl Future<Output = Result<SongMetadata, YtError>> + Send;
+
+    /// Fetches songs related to the given one.
+    fn fetch_related_song(
+        &self,
+        song_id: SongId,
+    ) -> impl Future<Output = Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError>> + Send;
+}
+
+/// The providers `YoutubeClient` can route requests to.
+enum Provider {
+    RustyPipe(RustyPipeProvider),
+    Invidious(InvidiousProvider),
+}
+
+impl SearchProvider for Provider {
+    fn name(&self) -> &'static str {
+        match self {
+            Provider::RustyPipe(p) => p.name(),
+            Provider::Invidious(p) => p.name(),
+        }
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SearchResult>, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.search(query).await,
+            Provider::Invidious(p) => p.search(query).await,
+        }
+    }
+
+    async fn suggestions(&self, prefix: &str) -> Result<Vec<String>, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.suggestions(prefix).await,
+            Provider::Invidious(p) => p.suggestions(prefix).await,
+        }
+    }
+
+    async fn stream(&self, id: &SongId) -> Result<(SongUrl, &'static str), YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.stream(id).await,
+            Provider::Invidious(p) => p.stream(id).await,
+        }
+    }
+
+    async fn fetch_playlist(
+        &self,
+        search_query: &str,
+    ) -> Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.fetch_playlist(search_query).await,
+            Provider::Invidious(p) => p.fetch_playlist(search_query).await,
+        }
+    }
+
+    async fn fetch_playlist_songs(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.fetch_playlist_songs(playlist_id).await,
+            Provider::Invidious(p) => p.fetch_playlist_songs(playlist_id).await,
+        }
+    }
+
+    async fn metadata(&self, id: &SongId) -> Result<SongMetadata, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.metadata(id).await,
+            Provider::Invidious(p) => p.metadata(id).await,
+        }
+    }
+
+    async fn fetch_related_song(
+        &self,
+        song_id: SongId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        match self {
+            Provider::RustyPipe(p) => p.fetch_related_song(song_id).await,
+            Provider::Invidious(p) => p.fetch_related_song(song_id).await,
+        }
+    }
+}
+
+/// Runs a request on the active provider. When that makes the provider fail
+/// `FAILOVER_AFTER` times in a row, the request is repeated on the other one.
+/// Missing songs and playlists are the provider working, not failing.
+macro_rules! failover {
+    ($client:ident, $call:ident($($arg:expr),*)) => {{
+        let result = $client.active().$call($($arg),*).await;
+        let working = result
+            .as_ref()
+            .err()
+            .is_none_or(|e| matches!(e, YtError::Unavailable(_)));
+        if $client.record(working) {
+            $client.active().$call($($arg),*).await
+        } else {
+            result
+        }
+    }};
+}
+
+/// Callback told the name of the provider requests switched to.
+type SwitchCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+/// A client for interacting with YouTube music.
+///
+/// Requests go to RustyPipe, or to an Invidious instance when `FEATHER_YT_BACKEND=invidious`.
+/// With `FEATHER_INVIDIOUS_URL` set, the other one serves as a fallback once the
+/// active provider keeps failing.
 pub struct YoutubeClient {
-    client: RustyPipeQuery,
+    primary: Provider,                   // Provider picked by configuration
+    fallback: Option<Provider>,          // Provider switched to when the active one keeps failing
+    on_fallback: AtomicBool,             // Whether requests currently go to the fallback
+    failures: AtomicU32,                 // Consecutive failures of the active provider
+    on_switch: OnceLock<SwitchCallback>, // Told the name of the new provider
+    metadata: OnceLock<MetadataCache>,   // Filled with the details search results come with
 }
 
 impl YoutubeClient {
-    /// Creates a new instance of `YoutubeClient`.
-    pub fn new() -> Self {
-        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
-        path.push("Feather");
-        let rp = RustyPipe::builder().storage_dir(path).build().unwrap();
-        let client = rp.query();
-        YoutubeClient { client }
+    /// Creates a new instance of `YoutubeClient` using the default data directory.
+    pub fn new() -> Result<Self, YtError> {
+        Self::new_at(&Paths::default().data_dir)
+    }
+
+    /// Creates a new instance of `YoutubeClient` that caches under the given base directory.
+    pub fn new_at(base: &Path) -> Result<Self, YtError> {
+        let rustypipe = Provider::RustyPipe(RustyPipeProvider::new_at(base)?);
+        let invidious = env::var("FEATHER_INVIDIOUS_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| Provider::Invidious(InvidiousProvider::new(&url)));
+        let prefer_invidious = env::var("FEATHER_YT_BACKEND")
+            .is_ok_and(|backend| backend.trim().eq_ignore_ascii_case("invidious"));
+        let (primary, fallback) = match invidious {
+            Some(invidious) if prefer_invidious => (invidious, Some(rustypipe)),
+            invidious => (rustypipe, invidious),
+        };
+        Ok(YoutubeClient {
+            primary,
+            fallback,
+            on_fallback: AtomicBool::new(false),
+            failures: AtomicU32::new(0),
+            on_switch: OnceLock::new(),
+            metadata: OnceLock::new(),
+        })
+    }
+
+    /// Registers a callback told the provider's name whenever requests switch providers.
+    /// Only the first registration is kept.
+    pub fn on_switch(&self, notify: impl Fn(&str) + Send + Sync + 'static) {
+        let _ = self.on_switch.set(Box::new(notify));
+    }
+
+    /// Registers the cache that the durations and thumbnails of search results are
+    /// stored in, sparing a lookup when one of them is played. Only the first
+    /// registration is kept.
+    pub fn cache_metadata(&self, cache: MetadataCache) {
+        let _ = self.metadata.set(cache);
+    }
+
+    /// Sends the cookies with YouTube requests made through RustyPipe.
+    /// Invidious instances use their own session, so they are left alone.
+    pub async fn set_cookies(&self, cookies: &CookieJar) -> Result<(), String> {
+        for provider in std::iter::once(&self.primary).chain(&self.fallback) {
+            if let Provider::RustyPipe(rustypipe) = provider {
+                rustypipe.set_cookies(cookies).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name of the provider requests currently go to.
+    pub fn active_provider(&self) -> &'static str {
+        self.active().name()
+    }
+
+    fn active(&self) -> &Provider {
+        match &self.fallback {
+            Some(fallback) if self.on_fallback.load(Ordering::Relaxed) => fallback,
+            _ => &self.primary,
+        }
+    }
+
+    /// Counts a request outcome, switching providers after too many failures in a row.
+    /// Returns whether it switched.
+    fn record(&self, ok: bool) -> bool {
+        if ok {
+            self.failures.store(0, Ordering::Relaxed);
+            return false;
+        }
+        if self.fallback.is_none()
+            || self.failures.fetch_add(1, Ordering::Relaxed) + 1 < FAILOVER_AFTER
+        {
+            return false;
+        }
+        self.failures.store(0, Ordering::Relaxed);
+        self.on_fallback.fetch_xor(true, Ordering::Relaxed);
+        if let Some(notify) = self.on_switch.get() {
+            notify(self.active().name());
+        }
+        true
     }
 
     /// Searches for music based on the given query.
-    /// Returns a vector of tuples where each entry contains a song name and ID,
-    /// along with a list of associated artist names.
-    pub async fn search(
+    /// Returns the songs found in ranking order, with their artists and whatever
+    /// details the search listed, which also go into the metadata cache.
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>, YtError> {
+        let results = with_retry(|| async move { failover!(self, search(query)) }).await?;
+        if let Some(cache) = self.metadata.get() {
+            for result in &results {
+                // Details looked up on their own are at least as complete, so they stay
+                if result.metadata.duration.is_some()
+                    && matches!(cache.get(&result.song_id), Ok(None))
+                {
+                    let _ = cache.set(&result.song_id, result.metadata.clone());
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Fetches search suggestions for a partially typed query.
+    /// Returns the suggested search terms in the order YouTube ranks them.
+    pub async fn suggestions(&self, prefix: &str) -> Result<Vec<String>, YtError> {
+        failover!(self, suggestions(prefix))
+    }
+
+    /// Fetches the audio stream URL for a given song ID.
+    pub async fn fetch_song_url(&self, id: &SongId) -> Result<SongUrl, YtError> {
+        failover!(self, stream(id)).map(|(url, _)| url)
+    }
+
+    /// Fetches the audio stream URL for a given song ID along with the file extension
+    /// matching the stream's container, for saving it to disk.
+    pub async fn fetch_song_download(
         &self,
-        query: &str,
-    ) -> Result<Vec<((SongName, SongId), Vec<ArtistName>)>, String> {
-        match self.client.music_search_main(query).await {
+        id: &SongId,
+    ) -> Result<(SongUrl, &'static str), YtError> {
+        failover!(self, stream(id))
+    }
+
+    /// Searches for playlists based on a given query.
+    /// Returns a hashmap where the key is the playlist name and the value is a tuple
+    /// containing the playlist ID and a list of associated channel names.
+    pub async fn fetch_playlist(
+        &self,
+        search_query: &str,
+    ) -> Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, YtError> {
+        with_retry(|| async move { failover!(self, fetch_playlist(search_query)) }).await
+    }
+
+    /// Fetches songs from a given playlist ID.
+    /// Returns a hashmap where each key is a tuple of (song name, song ID), and
+    /// the value is a list of associated artist names.
+    pub async fn fetch_playlist_songs(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        let playlist_id = &playlist_id;
+        with_retry(|| async move { failover!(self, fetch_playlist_songs(playlist_id.clone())) })
+            .await
+    }
+
+    /// Fetches the duration, channel and thumbnail of a song.
+    /// Callers cache the result, see `MetadataCache`.
+    pub async fn fetch_metadata(&self, id: &SongId) -> Result<SongMetadata, YtError> {
+        failover!(self, metadata(id))
+    }
+
+    /// Fetches related songs for a given song ID.
+    /// Returns a hashmap where each key is a tuple of (song name, song ID), and
+    /// the value is a list of associated artist names.
+    pub async fn fetch_related_song(
+        &self,
+        song_id: SongId,
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        failover!(self, fetch_related_song(song_id.clone()))
+    }
+}
+
+/// Talks to YouTube Music directly through RustyPipe.
+pub struct RustyPipeProvider {
+    rp: RustyPipe,
+    authenticated: AtomicBool, // Whether cookies were set for requests to use
+}
+
+impl RustyPipeProvider {
+    /// Creates a provider that caches under the given base directory and sends its
+    /// requests through the configured proxy.
+    pub fn new_at(base: &Path) -> Result<Self, YtError> {
+        let _ = std::fs::create_dir_all(base); // RustyPipe needs it to exist for its cache
+        let rp = RustyPipe::builder()
+            .storage_dir(base.to_path_buf())
+            .build_with_client(proxy::client_builder())?;
+        Ok(RustyPipeProvider {
+            rp,
+            authenticated: AtomicBool::new(false),
+        })
+    }
+
+    /// Signs requests in with the cookies of a YouTube session.
+    pub async fn set_cookies(&self, cookies: &CookieJar) -> Result<(), String> {
+        self.rp
+            .user_auth_set_cookie_txt(&cookies.to_netscape())
+            .await
+            .map_err(|e| format!("YouTube rejected the cookies: {}", e))?;
+        self.authenticated.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn query(&self) -> RustyPipeQuery {
+        let query = self.rp.query();
+        if self.authenticated.load(Ordering::Relaxed) {
+            query.authenticated()
+        } else {
+            query
+        }
+    }
+}
+
+/// Details of a YouTube Music track, which searches and lookups list alike.
+fn track_metadata(track: &TrackItem) -> SongMetadata {
+    SongMetadata {
+        duration: track.duration,
+        channel: track.artists.first().map(|artist| artist.name.clone()),
+        thumbnail: track
+            .cover
+            .iter()
+            .max_by_key(|thumbnail| thumbnail.width)
+            .map(|thumbnail| thumbnail.url.clone()),
+        fetched_at: 0, // Stamped by the cache
+    }
+}
+
+impl SearchProvider for RustyPipeProvider {
+    fn name(&self) -> &'static str {
+        "YouTube Music"
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<SearchResult>, YtError> {
+        match self.query().music_search_main(query).await {
             Ok(results) => {
                 let mut search_result = vec![];
 
                 for item in results.items.items {
                     if let MusicItem::Track(data) = item {
-                        let song_id_pair = (data.name, data.id);
-                        let artist_names: Vec<String> =
-                            data.artists.into_iter().map(|id| id.name).collect();
-                        search_result.push((song_id_pair, artist_names));
+                        let metadata = track_metadata(&data);
+                        search_result.push(SearchResult {
+                            song_name: data.name,
+                            song_id: data.id,
+                            artist_name: data.artists.into_iter().map(|id| id.name).collect(),
+                            metadata,
+                        });
                     }
                 }
 
                 Ok(search_result)
             }
-            Err(_) => Err("Error in Search Result".to_string()),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Fetches the audio stream URL for a given song ID.
-    pub async fn fetch_song_url(&self, id: &SongId) -> Result<SongUrl, String> {
-        match self.client.player(&id).await {
+    async fn suggestions(&self, prefix: &str) -> Result<Vec<String>, YtError> {
+        match self.query().search_suggestion(prefix).await {
+            Ok(terms) => Ok(terms),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn stream(&self, id: &SongId) -> Result<(SongUrl, &'static str), YtError> {
+        match self.query().player(id).await {
             Ok(player) => match player.select_audio_stream(&StreamFilter::default()) {
-                Some(stream) => return Ok(stream.url.clone()),
-                None => return Err("Audio Stream not Found".to_string()),
+                Some(stream) => {
+                    let extension = match stream.format {
+                        AudioFormat::M4a => "m4a",
+                        _ => "webm",
+                    };
+                    Ok((stream.url.clone(), extension))
+                }
+                None => Err(YtError::Unavailable("no audio stream".to_string())),
             },
-            Err(_) => return Err("Link cannot be Found".to_string()),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Searches for playlists based on a given query.
-    /// Returns a hashmap where the key is the playlist name and the value is a tuple
-    /// containing the playlist ID and a list of associated channel names.
-    pub async fn fetch_playlist(
+    async fn fetch_playlist(
         &self,
         search_query: &str,
-    ) -> Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, String> {
-        match self.client.music_search_playlists(search_query, true).await {
+    ) -> Result<HashMap<PlaylistName, (PlaylistId, Vec<ChannelName>)>, YtError> {
+        match self
+            .query()
+            .music_search_playlists(search_query, true)
+            .await
+        {
             Ok(playlists) => {
                 let mut result = HashMap::new();
 
@@ -83,18 +548,15 @@ impl YoutubeClient {
 
                 Ok(result)
             }
-            Err(e) => Err(format!("Error in fetching playlists: {}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Fetches songs from a given playlist ID.
-    /// Returns a hashmap where each key is a tuple of (song name, song ID), and
-    /// the value is a list of associated artist names.
-    pub async fn fetch_playlist_songs(
+    async fn fetch_playlist_songs(
         &self,
         playlist_id: PlaylistId,
-    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, String> {
-        match self.client.playlist(playlist_id).await {
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        match self.query().playlist(playlist_id).await {
             Ok(playlist_data) => {
                 let mut song_map = HashMap::new();
 
@@ -111,18 +573,20 @@ impl YoutubeClient {
 
                 Ok(song_map)
             }
-            Err(e) => Err(format!("Error fetching playlist songs: {}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 
-    /// Fetches related songs for a given song ID.
-    /// Returns a hashmap where each key is a tuple of (song name, song ID), and
-    /// the value is a list of associated artist names.
-    pub async fn fetch_related_song(
+    async fn metadata(&self, id: &SongId) -> Result<SongMetadata, YtError> {
+        let details = self.query().music_details(id).await?;
+        Ok(track_metadata(&details.track))
+    }
+
+    async fn fetch_related_song(
         &self,
         song_id: SongId,
-    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, String> {
-        match self.client.music_related(song_id).await {
+    ) -> Result<HashMap<(SongName, SongId), Vec<ArtistName>>, YtError> {
+        match self.query().music_related(song_id).await {
             Ok(music_list) => {
                 let tracks = music_list.tracks;
                 let mut results = HashMap::new();
@@ -137,7 +601,7 @@ impl YoutubeClient {
                 }
                 Ok(results)
             }
-            Err(_) => Err("Error finding related songs".to_string()),
+            Err(e) => Err(e.into()),
         }
     }
 }
@@ -215,3 +679,75 @@ impl YoutubeClient {
 //         Err(e) => eprintln!("Test failed: {}", e),
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    // Runs `with_retry` over canned answers, returning its result, how many requests
+    // it made and how long it waited in between
+    async fn retried(
+        answers: Vec<Result<u32, YtError>>,
+    ) -> (Result<u32, YtError>, usize, Duration) {
+        let total = answers.len();
+        let mut answers = answers.into_iter();
+        let started = Instant::now();
+        let result = with_retry(|| {
+            let answer = answers.next().expect("requested again after giving up");
+            async move { answer }
+        })
+        .await;
+        (result, total - answers.len(), started.elapsed())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limited_request_is_retried_after_a_backoff() {
+        let (result, requests, waited) =
+            retried(vec![Err(YtError::RateLimited(None)), Ok(7)]).await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(requests, 2);
+        assert!(waited >= RETRY_BASE_DELAY && waited <= RETRY_BASE_DELAY.mul_f64(1.5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_is_honoured() {
+        let wait = Duration::from_secs(10);
+        let (result, requests, waited) =
+            retried(vec![Err(YtError::RateLimited(Some(wait))), Ok(7)]).await;
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(requests, 2);
+        assert!(waited >= wait && waited < wait + Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn too_long_retry_after_is_reported() {
+        let wait = MAX_RETRY_AFTER + Duration::from_secs(1);
+        let (result, requests, waited) =
+            retried(vec![Err(YtError::RateLimited(Some(wait))), Ok(7)]).await;
+        assert!(matches!(result, Err(YtError::RateLimited(Some(_)))));
+        assert_eq!(requests, 1);
+        assert_eq!(waited, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_the_last_attempt() {
+        let answers = (0..RETRY_ATTEMPTS)
+            .map(|_| Err(YtError::Network("offline".to_string())))
+            .collect();
+        let (result, requests, _) = retried(answers).await;
+        assert!(matches!(result, Err(YtError::Network(_))));
+        assert_eq!(requests, RETRY_ATTEMPTS as usize);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lasting_errors_are_not_retried() {
+        let (result, requests, _) = retried(vec![
+            Err(YtError::Unavailable("private".to_string())),
+            Ok(7),
+        ])
+        .await;
+        assert!(matches!(result, Err(YtError::Unavailable(_))));
+        assert_eq!(requests, 1);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::debug_log;
+use crate::redraw::Redraw;
+use crate::theme::muted_style;
+use feather::SongId;
+use feather::backend::Backend;
+use feather::proxy;
+use image::GrayImage;
+use image::imageops::FilterType;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::widgets::{Paragraph, Widget};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const RAMP: &[u8] = b" .:-=+*#%@"; // Characters from darkest to brightest pixel
+const DEFAULT_WIDTH: u16 = 24; // Columns the art may take when FEATHER_ALBUM_ART_WIDTH is unset
+const MIN_WIDTH: u16 = 6; // Narrower art is unrecognisable, so none is drawn
+const DECODED_SIZE: u32 = 96; // Thumbnails are shrunk to this many pixels across once downloaded
+const MAX_CACHED: usize = 64; // Songs kept before the cache starts over
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// What is known about a song's thumbnail
+enum Art {
+    Loading,                                       // Download or decode is in progress
+    Missing,                                       // No thumbnail, or it could not be read
+    Ready(GrayImage, Option<(Rect, Vec<String>)>), // Thumbnail and its last rendering
+}
+
+// The current song's thumbnail drawn as ASCII art, enabled with FEATHER_ALBUM_ART=1.
+// Thumbnails are fetched in the background and failures only leave the space empty.
+pub struct AlbumArt {
+    backend: Arc<Backend>, // Thumbnail URLs from the metadata cache
+    http: reqwest::Client, // Downloads thumbnails
+    max_width: u16,        // Most columns the art may take
+    cache: Arc<Mutex<HashMap<SongId, Art>>>, // Art by song, with its last rendering
+    redraw: Redraw,        // Wakes the render loop once a thumbnail is ready
+}
+
+impl AlbumArt {
+    pub fn new(backend: Arc<Backend>, redraw: Redraw) -> Option<Self> {
+        if env::var("FEATHER_ALBUM_ART").as_deref() != Ok("1") {
+            return None;
+        }
+        let max_width = env::var("FEATHER_ALBUM_ART_WIDTH")
+            .ok()
+            .and_then(|width| width.trim().parse().ok())
+            .unwrap_or(DEFAULT_WIDTH);
+        let http = proxy::client_builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Some(Self {
+            backend,
+            http,
+            max_width,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            redraw,
+        })
+    }
+
+    // Columns the art of a song takes when drawn `height` rows tall, 0 when there is none yet
+    pub fn width(&self, song_id: &SongId, height: u16) -> u16 {
+        let Ok(mut cache) = self.cache.lock() else {
+            return 0;
+        };
+        let Some(art) = cache.get(song_id) else {
+            self.load(&mut cache, song_id);
+            return 0;
+        };
+        let Art::Ready(image, _) = art else {
+            return 0;
+        };
+        // Terminal cells are about twice as tall as they are wide
+        let width = (height as u32 * 2 * image.width() / image.height().max(1)) as u16;
+        let width = width.min(self.max_width);
+        if width < MIN_WIDTH { 0 } else { width }
+    }
+
+    // Starts fetching a song's thumbnail
+    fn load(&self, cache: &mut HashMap<SongId, Art>, song_id: &SongId) {
+        if cache.len() >= MAX_CACHED {
+            cache.clear();
+        }
+        cache.insert(song_id.clone(), Art::Loading);
+        let backend = Arc::clone(&self.backend);
+        let http = self.http.clone();
+        let shared = Arc::clone(&self.cache);
+        let redraw = self.redraw.clone();
+        let song_id = song_id.clone();
+        tokio::spawn(async move {
+            let art = match fetch(&backend, &http, &song_id).await {
+                Ok(Some(image)) => Art::Ready(image, None),
+                Ok(None) => Art::Missing,
+                Err(e) => {
+                    debug_log::log(&format!("album art: {}: {}", song_id, e));
+                    Art::Missing
+                }
+            };
+            if let Ok(mut cache) = shared.lock() {
+                cache.insert(song_id, art);
+            }
+            redraw.request();
+        });
+    }
+
+    // Draws a song's art filling the area, if its thumbnail is ready
+    pub fn render(&self, song_id: &SongId, area: Rect, buf: &mut Buffer) {
+        if area.width < MIN_WIDTH || area.height == 0 {
+            return;
+        }
+        let Ok(mut cache) = self.cache.lock() else {
+            return;
+        };
+        let Some(Art::Ready(image, rendered)) = cache.get_mut(song_id) else {
+            return;
+        };
+        // Only resize again when the player changed size
+        if rendered.as_ref().is_none_or(|(size, _)| *size != area) {
+            *rendered = Some((area, to_ascii(image, area.width, area.height)));
+        }
+        if let Some((_, lines)) = rendered {
+            Paragraph::new(lines.join("\n"))
+                .style(muted_style())
+                .render(area, buf);
+        }
+    }
+}
+
+// Downloads and decodes a song's thumbnail, None when it has none
+async fn fetch(
+    backend: &Backend,
+    http: &reqwest::Client,
+    song_id: &SongId,
+) -> Result<Option<GrayImage>, String> {
+    let Some(url) = backend
+        .metadata(song_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .and_then(|metadata| metadata.thumbnail)
+    else {
+        return Ok(None);
+    };
+    let bytes = http
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    // Decoding is CPU-bound, so it stays off the runtime's threads
+    tokio::task::spawn_blocking(move || {
+        let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+        Ok(Some(
+            image.thumbnail(DECODED_SIZE, DECODED_SIZE).into_luma8(),
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Maps each cell of a width × height grid to a character by the brightness under it
+fn to_ascii(image: &GrayImage, width: u16, height: u16) -> Vec<String> {
+    let scaled = image::imageops::resize(image, width as u32, height as u32, FilterType::Triangle);
+    scaled
+        .rows()
+        .map(|row| {
+            row.map(|pixel| {
+                let level = pixel.0[0] as usize * (RAMP.len() - 1) / 255;
+                RAMP[level] as char
+            })
+            .collect()
+        })
+        .collect()
+}
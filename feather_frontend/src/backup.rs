@@ -0,0 +1,115 @@
+use crate::debug_log;
+use feather::archive::{self, ArchiveError, Snapshot};
+use feather::database::HistoryDB;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+const DEFAULT_KEEP: usize = 5; // Backups kept when FEATHER_BACKUP_KEEP is unset
+const PREFIX: &str = "feather-"; // Backup files are named feather-<unix time>.json
+const EXTENSION: &str = ".json";
+
+// Exports the history into <data dir>/backups every FEATHER_AUTO_BACKUP_HOURS hours,
+// in the format of `feather_frontend export`, so a corrupted database can be restored
+// with `import`. Only the newest FEATHER_BACKUP_KEEP backups are kept, and nothing is
+// written while the data is unchanged since the last backup.
+pub struct AutoBackup {
+    task: JoinHandle<()>, // Backs up on a timer until the app exits
+}
+
+impl AutoBackup {
+    pub fn start(history: Arc<HistoryDB>, data_dir: &Path) -> Option<Self> {
+        let hours = env::var("FEATHER_AUTO_BACKUP_HOURS")
+            .ok()
+            .and_then(|hours| hours.trim().parse::<u64>().ok())
+            .filter(|hours| *hours > 0)?;
+        let keep = env::var("FEATHER_BACKUP_KEEP")
+            .ok()
+            .and_then(|keep| keep.trim().parse::<usize>().ok())
+            .filter(|keep| *keep > 0)
+            .unwrap_or(DEFAULT_KEEP);
+        let dir = data_dir.join("backups");
+        let every = Duration::from_secs(hours * 60 * 60);
+        Some(Self {
+            task: tokio::spawn(run(history, dir, every, keep)),
+        })
+    }
+}
+
+impl Drop for AutoBackup {
+    fn drop(&mut self) {
+        self.task.abort(); // Writes finish with a rename, so none is left half done
+    }
+}
+
+async fn run(history: Arc<HistoryDB>, dir: PathBuf, every: Duration, keep: usize) {
+    // Compared with the newest backup on disk, so restarting does not repeat it
+    let mut last = backups(&dir)
+        .last()
+        .and_then(|path| fs::read(path).ok())
+        .map(|bytes| archive::hash(&bytes));
+    let mut timer = interval(every); // The first tick is right away
+    loop {
+        timer.tick().await;
+        match back_up(&history, &dir, last) {
+            Ok(Some(hash)) => last = Some(hash),
+            Ok(None) => debug_log::log("backup: skipped, nothing changed since the last one"),
+            Err(ArchiveError::Busy) => debug_log::log("backup: skipped, an export is in progress"),
+            Err(e) => debug_log::log(&format!("backup: {}", e)),
+        }
+        prune(&dir, keep);
+    }
+}
+
+// Writes a backup unless the data hashes as `last`, returning the new backup's hash
+fn back_up(
+    history: &HistoryDB,
+    dir: &Path,
+    last: Option<u64>,
+) -> Result<Option<u64>, ArchiveError> {
+    let snapshot = Snapshot::take(history)?;
+    let hash = snapshot.hash();
+    if last == Some(hash) {
+        return Ok(None);
+    }
+    fs::create_dir_all(dir)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    snapshot.write(&dir.join(format!("{}{}{}", PREFIX, now, EXTENSION)))?;
+    Ok(Some(hash))
+}
+
+// Backups in the directory, oldest first
+fn backups(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(PREFIX) && name.ends_with(EXTENSION))
+        })
+        .collect();
+    // Times have the same number of digits for centuries, so names sort by age
+    backups.sort();
+    backups
+}
+
+// Removes all but the newest `keep` backups
+fn prune(dir: &Path, keep: usize) {
+    let backups = backups(dir);
+    let excess = backups.len().saturating_sub(keep);
+    for path in &backups[..excess] {
+        if let Err(e) = fs::remove_file(path) {
+            debug_log::log(&format!("backup: cannot remove {}: {}", path.display(), e));
+        }
+    }
+}
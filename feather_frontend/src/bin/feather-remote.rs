@@ -0,0 +1,45 @@
+// Sends one command to a running Feather over its IPC socket and prints the reply,
+// e.g. `feather-remote play-pause` bound to a window manager hotkey
+#[cfg(unix)]
+use feather_frontend::ipc::{COMMANDS, send, socket_path};
+#[cfg(unix)]
+use std::env;
+use std::process::ExitCode;
+
+// The socket is a Unix domain socket, which Feather only opens on Unix
+#[cfg(not(unix))]
+fn main() -> ExitCode {
+    eprintln!("feather-remote is only supported on Unix");
+    ExitCode::FAILURE
+}
+
+#[cfg(unix)]
+fn main() -> ExitCode {
+    let command = env::args().skip(1).collect::<Vec<_>>().join(" ");
+    if command.is_empty() || command == "-h" || command == "--help" {
+        println!("Usage: feather-remote <command>\n\nCommands: {}", COMMANDS);
+        return ExitCode::SUCCESS;
+    }
+
+    let reply = match send(&command) {
+        Ok(reply) => reply,
+        Err(e) => {
+            eprintln!(
+                "Cannot reach Feather at {} ({}). Is it running with FEATHER_IPC=1?",
+                socket_path().display(),
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    match reply.strip_prefix("error: ") {
+        Some(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+        None => {
+            println!("{}", reply);
+            ExitCode::SUCCESS
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::search::{deprioritized_keywords, rank_results, song_of};
+use feather::backend::{Backend, Song};
+use feather::{archive, cookies::CookieSource, database::HistoryDB, paths::Paths};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{Instant, sleep};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500); // How often mpv is asked whether it finished
+const START_TIMEOUT: Duration = Duration::from_secs(30); // Longest wait for the stream to start
+
+// Subcommands that run without the TUI
+pub enum Command {
+    Play(String),    // Search and play the first result
+    History,         // Print the history
+    Export(PathBuf), // Save the history to an archive
+    Import(PathBuf), // Merge an archive into the history
+    Stats,           // Print what the database holds
+    Compact,         // Flush the database and print its size before and after
+}
+
+// Runs a subcommand, returning the message to print when it fails
+pub async fn run(
+    command: Command,
+    paths: &Paths,
+    cookies: Option<CookieSource>,
+    incognito: bool,
+) -> Result<(), String> {
+    let history = Arc::new(
+        HistoryDB::new_at(&paths.data_dir).map_err(|e| format!("Failed to open history: {}", e))?,
+    );
+    match command {
+        Command::History => print_history(&history),
+        Command::Export(file) => {
+            let count = archive::export(&history, &file).map_err(|e| e.to_string())?;
+            println!("Exported {} history entries to {}", count, file.display());
+            Ok(())
+        }
+        Command::Stats => print_stats(&history),
+        Command::Compact => {
+            let before = history.stats().map_err(|e| e.to_string())?.size_on_disk;
+            history.flush().map_err(|e| e.to_string())?;
+            let after = history.stats().map_err(|e| e.to_string())?.size_on_disk;
+            println!(
+                "Flushed the database: {} before, {} now",
+                format_size(before),
+                format_size(after)
+            );
+            // sled 0.34 has no compaction call of its own
+            println!("Older segments are reclaimed by sled in the background as they empty.");
+            Ok(())
+        }
+        Command::Import(file) => {
+            let count = archive::import(&history, &file).map_err(|e| e.to_string())?;
+            history.flush().map_err(|e| e.to_string())?;
+            println!("Imported {} history entries from {}", count, file.display());
+            Ok(())
+        }
+        Command::Play(query) => {
+            let backend = Backend::new(history, paths, cookies).map_err(|e| e.to_string())?;
+            backend.set_incognito(incognito);
+            if backend.has_cookies() {
+                backend.reload_cookies().await.map_err(|e| e.to_string())?;
+            }
+            let played = play(&backend, &query).await;
+            // Flushed even when playback failed, the song may already be in the history
+            backend.shutdown().map_err(|e| e.to_string())?;
+            played
+        }
+    }
+}
+
+// Prints the history, most recent first, one "Title - Artists" line per song
+fn print_history(history: &HistoryDB) -> Result<(), String> {
+    let entries = history.get_history().map_err(|e| e.to_string())?;
+    for entry in entries {
+        println!("{} - {}", entry.song_name, entry.artist_name.join(", "));
+    }
+    Ok(())
+}
+
+// Searches YouTube and returns the result the search view would list first
+pub async fn best_match(backend: &Backend, query: &str) -> Result<Song, String> {
+    let results = backend.yt.search(query).await.map_err(|e| e.to_string())?;
+    let Some(first) = rank_results(results, &deprioritized_keywords())
+        .into_iter()
+        .next()
+    else {
+        return Err(format!("No results for \"{}\"", query));
+    };
+    Ok(song_of(&first))
+}
+
+// Prints the database's size, entries per tree and last flush, one per line
+fn print_stats(history: &HistoryDB) -> Result<(), String> {
+    let stats = history.stats().map_err(|e| e.to_string())?;
+    println!("Size on disk: {}", format_size(stats.size_on_disk));
+    for (tree, entries) in &stats.trees {
+        println!("{:<16} {} entries", tree, entries);
+    }
+    let last_flush = match stats.last_flush {
+        Some(time) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0);
+            format!("{} ago", format_age(now.saturating_sub(time)))
+        }
+        None => "never".to_string(),
+    };
+    println!("Last flush: {}", last_flush);
+    Ok(())
+}
+
+// Formats a byte count with a binary unit, e.g. "1.5 MiB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+// Formats a duration in seconds by its largest unit, e.g. "3 h"
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{} s", seconds),
+        60..3600 => format!("{} min", seconds / 60),
+        3600..86400 => format!("{} h", seconds / 3600),
+        _ => format!("{} days", seconds / 86400),
+    }
+}
+
+// Plays the best result for the query and stays until the song ends or Ctrl+C is pressed
+async fn play(backend: &Backend, query: &str) -> Result<(), String> {
+    backend.player.set_loop(false).map_err(|e| e.to_string())?; // Let the song end
+    let song = best_match(backend, query).await?;
+    println!(
+        "Playing {} - {}",
+        song.song_name,
+        song.artist_name.join(", ")
+    );
+    backend.play_music(song).await.map_err(|e| e.to_string())?;
+
+    tokio::select! {
+        finished = wait_until_finished(backend) => finished,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+// Waits for mpv to load the stream and then to go idle again once it ends
+async fn wait_until_finished(backend: &Backend) -> Result<(), String> {
+    let deadline = Instant::now() + START_TIMEOUT;
+    while backend.player.is_idle() {
+        if Instant::now() >= deadline {
+            return Err("Playback did not start".to_string());
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+    while !backend.player.is_idle() {
+        sleep(POLL_INTERVAL).await;
+    }
+    Ok(())
+}
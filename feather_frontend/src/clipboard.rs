@@ -0,0 +1,38 @@
+use arboard::Clipboard;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::env;
+use std::io::{Write, stdout};
+use std::sync::Mutex;
+
+// Kept alive for the whole session: on X11 the copied text is served by this handle
+// and disappears as soon as it is dropped
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+// Copies text to the system clipboard, falling back to OSC 52 so the local
+// terminal receives it over SSH sessions
+pub fn copy(text: &str) -> Result<(), String> {
+    let over_ssh = env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some();
+    if !over_ssh && copy_native(text).is_ok() {
+        return Ok(());
+    }
+    copy_osc52(text)
+}
+
+// Copies text using the native clipboard of the machine Feather runs on
+fn copy_native(text: &str) -> Result<(), String> {
+    let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+    if guard.is_none() {
+        *guard = Some(Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    match guard.as_mut() {
+        Some(clipboard) => clipboard.set_text(text).map_err(|e| e.to_string()),
+        None => Err("Clipboard unavailable".to_string()),
+    }
+}
+
+// Asks the terminal emulator to set its clipboard via the OSC 52 escape sequence
+fn copy_osc52(text: &str) -> Result<(), String> {
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text)).map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())
+}
@@ -0,0 +1,68 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FRAME_REPORT: u32 = 100; // Frames summed up per logged render time
+
+// Appends a line to the file named by FEATHER_DEBUG_LOG, if set.
+// The TUI owns the terminal, so background failures that should not reach the
+// status bar are recorded here instead of being printed.
+pub fn log(message: &str) {
+    let Some(path) = env::var_os("FEATHER_DEBUG_LOG") else {
+        return;
+    };
+    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) else {
+        return;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let _ = writeln!(file, "{} {}", now, message);
+}
+
+// Sums up how long frames take to render and logs the mean and the slowest of every
+// FRAME_REPORT frames, e.g. to see whether a view reads too much per frame
+pub struct FrameTimer {
+    enabled: bool,     // Whether FEATHER_DEBUG_LOG is set, checked once
+    frames: u32,       // Frames recorded since the last report
+    total: Duration,   // Their render time together
+    slowest: Duration, // The longest of them
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self {
+            enabled: env::var_os("FEATHER_DEBUG_LOG").is_some(),
+            frames: 0,
+            total: Duration::ZERO,
+            slowest: Duration::ZERO,
+        }
+    }
+
+    // Records the render time of one frame
+    pub fn record(&mut self, elapsed: Duration) {
+        if !self.enabled {
+            return;
+        }
+        self.frames += 1;
+        self.total += elapsed;
+        self.slowest = self.slowest.max(elapsed);
+        if self.frames == FRAME_REPORT {
+            log(&format!(
+                "render: {} frames, mean {:?}, slowest {:?}",
+                self.frames,
+                self.total / self.frames,
+                self.slowest
+            ));
+            *self = Self::new();
+        }
+    }
+}
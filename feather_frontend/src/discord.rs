@@ -0,0 +1,137 @@
+use crate::debug_log;
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient, activity};
+use feather::backend::{Backend, Song};
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1); // How often the playing state is checked
+const MIN_BACKOFF: Duration = Duration::from_secs(5); // First wait after Discord was unreachable
+const MAX_BACKOFF: Duration = Duration::from_secs(300); // Longest wait between connection attempts
+
+// What the presence currently shows, so Discord is only contacted on changes
+#[derive(PartialEq)]
+enum Shown {
+    Nothing,
+    Song(String, bool), // Song id and whether it is paused
+}
+
+// Shows "Listening to <title>" in Discord while a song plays.
+// Enabled by FEATHER_DISCORD_CLIENT_ID, the id of a Discord application whose name
+// is displayed above the song. Discord being closed or unreachable is only logged.
+pub struct DiscordPresence {
+    stop: Arc<AtomicBool>, // Asks the worker to clear the presence and exit
+    thread: Option<JoinHandle<()>>, // Worker talking to the Discord client
+}
+
+impl DiscordPresence {
+    pub fn start(backend: Arc<Backend>) -> Option<Self> {
+        let client_id = env::var("FEATHER_DISCORD_CLIENT_ID").ok()?;
+        let client_id = client_id.trim().to_string();
+        if client_id.is_empty() {
+            return None;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            thread::Builder::new()
+                .name("discord".to_string())
+                .spawn(move || run(backend, &client_id, &stop))
+                .ok()?
+        };
+        Some(Self {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for DiscordPresence {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Keeps the presence in line with the player, reconnecting with backoff when Discord is away
+fn run(backend: Arc<Backend>, client_id: &str, stop: &AtomicBool) {
+    let mut songs = backend.subscribe();
+    let mut client: Option<DiscordIpcClient> = None;
+    let mut shown = Shown::Nothing;
+    let mut backoff = MIN_BACKOFF;
+    let mut next_attempt = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        if client.is_none() && Instant::now() >= next_attempt {
+            match connect(client_id) {
+                Ok(connected) => {
+                    client = Some(connected);
+                    shown = Shown::Nothing; // A fresh connection shows nothing yet
+                    backoff = MIN_BACKOFF;
+                }
+                Err(e) => {
+                    debug_log::log(&format!("discord: connect failed: {}", e));
+                    next_attempt = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+
+        if let Some(connected) = client.as_mut() {
+            let song = songs.borrow_and_update().clone();
+            let playing = backend.player.is_playing();
+            let wanted = match (&song, playing) {
+                (Some(song), Ok(playing)) => Shown::Song(song.song_id.clone(), !playing),
+                _ => Shown::Nothing,
+            };
+            if wanted != shown {
+                let result = match (&song, &wanted) {
+                    (Some(song), Shown::Song(_, false)) => show(connected, song, &backend),
+                    _ => connected.clear_activity().map_err(|e| e.to_string()),
+                };
+                match result {
+                    Ok(()) => shown = wanted,
+                    Err(e) => {
+                        debug_log::log(&format!("discord: update failed: {}", e));
+                        client = None; // Reconnect on the next round
+                        next_attempt = Instant::now() + backoff;
+                    }
+                }
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    if let Some(mut connected) = client {
+        let _ = connected.clear_activity();
+        let _ = connected.close();
+    }
+}
+
+fn connect(client_id: &str) -> Result<DiscordIpcClient, String> {
+    let mut client = DiscordIpcClient::new(client_id).map_err(|e| e.to_string())?;
+    client.connect().map_err(|e| e.to_string())?;
+    Ok(client)
+}
+
+// Publishes the song with a start time derived from the playback position,
+// so Discord's elapsed timer matches the player
+fn show(client: &mut DiscordIpcClient, song: &Song, backend: &Backend) -> Result<(), String> {
+    let position = backend.player.position_secs() as i64;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let artists = format!("by {}", song.artist_name.join(", "));
+    let activity = activity::Activity::new()
+        .activity_type(activity::ActivityType::Listening)
+        .details(&song.song_name)
+        .state(&artists)
+        .timestamps(activity::Timestamps::new().start(now - position));
+    client.set_activity(activity).map_err(|e| e.to_string())
+}
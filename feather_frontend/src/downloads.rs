@@ -0,0 +1,338 @@
+use crate::list_nav::ListNav;
+use crate::player::spawn_play;
+use crate::redraw::Redraw;
+use crate::status_bar::{ERROR_DURATION, MESSAGE_DURATION, StatusBar};
+use crate::text::truncate_to_width;
+use crate::theme::{NOW_PLAYING, border_style, muted_style, selected_style, text_style};
+use crossterm::event::{KeyCode, KeyEvent};
+use feather::backend::{Backend, Song, Source};
+use feather::database::{Download, DownloadStatus};
+use feather::proxy;
+use ratatui::prelude::{Buffer, Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
+use ratatui::widgets::{
+    Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+// Directory downloads are saved to, FEATHER_DOWNLOAD_DIR or <music dir>/Feather
+pub fn download_dir() -> PathBuf {
+    match env::var_os("FEATHER_DOWNLOAD_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => dirs::audio_dir()
+            .or_else(|| dirs::home_dir().map(|home| home.join("Music")))
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join("Feather"),
+    }
+}
+
+// Saves a song for offline playback in the background, reporting progress in the status bar
+pub fn start_download(backend: Arc<Backend>, song: Song, status: StatusBar, redraw: Redraw) {
+    if let Source::Local(_) = song.source() {
+        status.set_message("Local files are already on disk", MESSAGE_DURATION);
+        return;
+    }
+    if let Ok(Some(existing)) = backend.downloads.get(&song.song_id) {
+        match existing.status {
+            DownloadStatus::Queued | DownloadStatus::Downloading => {
+                status.set_message("Already downloading", MESSAGE_DURATION);
+                return;
+            }
+            DownloadStatus::Done if existing.path.exists() => {
+                status.set_message("Already downloaded", MESSAGE_DURATION);
+                return;
+            }
+            _ => (), // Failed or deleted behind our back, try again
+        }
+    }
+    // Recorded right away so a second press while the URL is fetched is recognised
+    let dir = download_dir();
+    let record = Download {
+        song_name: song.song_name.clone(),
+        song_id: song.song_id.clone(),
+        artist_name: song.artist_name.clone(),
+        path: dir.join(&song.song_id),
+        status: DownloadStatus::Queued,
+    };
+    if let Err(e) = backend.downloads.set(&record) {
+        status.set_error(format!("Failed to queue download: {}", e), ERROR_DURATION);
+        return;
+    }
+    status.set_message(format!("Queued \"{}\"", song.song_name), MESSAGE_DURATION);
+    tokio::spawn(async move {
+        match download(&backend, record, &dir, &status, &redraw).await {
+            Ok(()) => status.set_message(
+                format!("Downloaded \"{}\"", song.song_name),
+                MESSAGE_DURATION,
+            ),
+            Err(e) => {
+                if let Ok(Some(mut failed)) = backend.downloads.get(&song.song_id) {
+                    failed.status = DownloadStatus::Failed;
+                    let _ = backend.downloads.set(&failed);
+                }
+                status.set_error(
+                    format!("Download of \"{}\" failed: {}", song.song_name, e),
+                    ERROR_DURATION,
+                );
+            }
+        }
+        redraw.request();
+    });
+}
+
+// Streams the audio into "<id>.<ext>.part" and renames it once complete,
+// so an interrupted download is never mistaken for a playable file
+async fn download(
+    backend: &Backend,
+    mut record: Download,
+    dir: &Path,
+    status: &StatusBar,
+    redraw: &Redraw,
+) -> Result<(), String> {
+    let (url, extension) = backend
+        .yt
+        .fetch_song_download(&record.song_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    record.path = dir.join(format!("{}.{}", record.song_id, extension));
+    record.status = DownloadStatus::Downloading;
+    backend.downloads.set(&record).map_err(|e| e.to_string())?;
+
+    let partial = dir.join(format!("{}.{}.part", record.song_id, extension));
+    let http = proxy::client_builder().build().map_err(|e| e.to_string())?;
+    let mut response = http
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let total = response.content_length().filter(|&total| total > 0);
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut written = 0;
+    let mut shown = None; // Last percentage put in the status bar
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        written += chunk.len() as u64;
+        if let Some(total) = total {
+            let percent = written * 100 / total;
+            if shown != Some(percent) {
+                shown = Some(percent);
+                status.set_message(
+                    format!("Downloading \"{}\" {}%", record.song_name, percent),
+                    MESSAGE_DURATION,
+                );
+                redraw.request();
+            }
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    tokio::fs::rename(&partial, &record.path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    record.status = DownloadStatus::Done;
+    backend.downloads.set(&record).map_err(|e| e.to_string())
+}
+
+// Lists downloaded songs with their status; Enter plays, d deletes
+pub struct DownloadsView {
+    nav: ListNav,                     // Selection within the downloads list
+    downloads: Option<Vec<Download>>, // Records as last read, None if reading failed
+    loaded: Option<u64>,              // Downloads generation `downloads` was read at
+    selected: Option<Download>,       // Download under the cursor
+    backend: Arc<Backend>,            // Owns the downloads record and plays songs
+    status: StatusBar,                // Action confirmations and playback errors
+    redraw: Redraw,                   // Wakes the render loop when playback starts or fails
+}
+
+impl DownloadsView {
+    pub fn new(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Self {
+        // Downloads cut short by the last exit will never finish, offer them for a retry
+        if let Ok(downloads) = backend.downloads.list() {
+            for mut download in downloads {
+                if matches!(
+                    download.status,
+                    DownloadStatus::Queued | DownloadStatus::Downloading
+                ) {
+                    download.status = DownloadStatus::Failed;
+                    let _ = backend.downloads.set(&download);
+                }
+            }
+        }
+        Self {
+            nav: ListNav::new(),
+            downloads: None,
+            loaded: None,
+            selected: None,
+            backend,
+            status,
+            redraw,
+        }
+    }
+
+    // Selects the row under a left click, returning whether a row was hit
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        self.nav.click(column, row)
+    }
+
+    // Moves the selection with the mouse wheel
+    pub fn scroll(&mut self, down: bool) {
+        self.nav.scroll(down)
+    }
+
+    // Count typed before the next list motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.nav.pending_count()
+    }
+
+    // Handles keyboard input for navigation and actions, returning whether the key was used
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
+        if self.nav.handle_key(key) {
+            return true; // List movement
+        }
+        match key.code {
+            KeyCode::Enter => {
+                // Play the selected song, from disk when its download finished
+                if let Some(download) = &self.selected {
+                    let song = Song::new(
+                        download.song_name.clone(),
+                        download.song_id.clone(),
+                        download.artist_name.clone(),
+                    );
+                    spawn_play(
+                        self.backend.clone(),
+                        song,
+                        self.status.clone(),
+                        self.redraw.clone(),
+                    );
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                // Delete the file and forget the download
+                if let Some(download) = self.selected.take() {
+                    if download.status == DownloadStatus::Downloading {
+                        self.status
+                            .set_message("Wait for the download to finish", MESSAGE_DURATION);
+                        return true;
+                    }
+                    let removed = match std::fs::remove_file(&download.path) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                        _ => self
+                            .backend
+                            .downloads
+                            .remove(&download.song_id)
+                            .map_err(|e| e.to_string()),
+                    };
+                    self.status.report(
+                        removed.map_err(|e| format!("Failed to delete download: {}", e)),
+                        format!("Deleted \"{}\"", download.song_name),
+                    );
+                }
+            }
+            _ => return false, // Left to the global bindings
+        }
+        true
+    }
+
+    // Reads the downloads again if they changed since the last read
+    fn refresh(&mut self) {
+        let generation = self.backend.downloads.generation();
+        if self.loaded == Some(generation) {
+            return;
+        }
+        self.downloads = self.backend.downloads.list().ok();
+        self.loaded = Some(generation);
+    }
+
+    // Renders the downloads list, highlighting its borders when focused
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new(format!("Downloads ({})", download_dir().display()))
+            .style(text_style())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style(focused)),
+            )
+            .render(chunks[0], buf);
+
+        let list_area = chunks[1];
+        self.refresh();
+        let downloads = match &self.downloads {
+            Some(downloads) => downloads,
+            None => {
+                self.nav.set_len(0);
+                Paragraph::new("Failed to load downloads").render(list_area, buf);
+                return;
+            }
+        };
+        self.nav.set_len(downloads.len());
+        self.nav.set_page_height(list_area.height);
+        self.selected = downloads.get(self.nav.selected()).cloned();
+        // Columns left for the text after borders and highlight symbol
+        let row_width = list_area.width.saturating_sub(3) as usize;
+        let playing = self.backend.current_song_id();
+
+        let items: Vec<ListItem> = downloads
+            .iter()
+            .enumerate()
+            .map(|(i, download)| {
+                let style = match download.status {
+                    _ if i == self.nav.selected() => selected_style(),
+                    DownloadStatus::Done => Style::default(),
+                    _ => muted_style(), // Not playable offline (yet)
+                };
+                let marker = if playing.as_ref() == Some(&download.song_id) {
+                    NOW_PLAYING
+                } else {
+                    ""
+                };
+                let state = match download.status {
+                    DownloadStatus::Queued => " [queued]",
+                    DownloadStatus::Downloading => " [downloading]",
+                    DownloadStatus::Failed => " [failed]",
+                    DownloadStatus::Done => "",
+                };
+                let text = format!(
+                    "{}{} - {}{}",
+                    marker,
+                    download.song_name,
+                    download.artist_name.join(", "),
+                    state
+                );
+                ListItem::new(Span::styled(truncate_to_width(&text, row_width), style))
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(self.nav.selected()));
+        StatefulWidget::render(
+            List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(border_style(focused)),
+                )
+                .highlight_symbol("▶"),
+            list_area,
+            buf,
+            &mut list_state,
+        );
+        self.nav.set_area(list_area, list_state.offset());
+        self.nav.render_scrollbar(list_area, buf);
+    }
+}
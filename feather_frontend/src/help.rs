@@ -0,0 +1,193 @@
+use crate::list_nav::ListNav;
+use crate::theme::{accent_style, selected_style};
+use crossterm::event::KeyEvent;
+use ratatui::prelude::{Buffer, Constraint, Rect};
+use ratatui::style::Modifier;
+use ratatui::widgets::{Block, Borders, Cell, Row, StatefulWidget, Table, TableState};
+
+// Key bindings of one context, in the order they are listed
+struct Section {
+    title: &'static str,
+    bindings: &'static [(&'static str, &'static str)],
+}
+
+const SECTIONS: &[Section] = &[
+    Section {
+        title: "Global",
+        bindings: &[
+            ("s", "Search"),
+            ("h", "History"),
+            ("p", "Player"),
+            ("d", "Downloads"),
+            ("l", "Local music"),
+            ("< / >", "Shrink / grow the Search pane"),
+            ("C", "Reload YouTube cookies"),
+            ("Ctrl+Space", "Pause / resume, from any view"),
+            ("Ctrl+N", "Play a related song, from any view"),
+            ("Ctrl+P", "Restart the song, from any view"),
+            ("?", "Help for the current view"),
+            ("Esc", "Quit application"),
+        ],
+    },
+    Section {
+        title: "Lists (Search Results / History)",
+        bindings: &[
+            ("↑ / k", "Navigate up in list"),
+            ("↓ / j", "Navigate down in list"),
+            ("g / G, Home / End", "Jump to first / last item"),
+            ("PgUp / PgDn", "Move a full page"),
+            ("Ctrl+U / Ctrl+D", "Move half a page"),
+            ("<count> j / k", "Move down / up several rows, e.g. 5j"),
+            ("<count> G", "Jump to that item, e.g. 10G"),
+            ("Esc", "Switch to Global Mode"),
+            ("?", "Help for the current view"),
+        ],
+    },
+    Section {
+        title: "Search Bar",
+        bindings: &[
+            ("Enter", "Search"),
+            ("Alt+Enter", "Search and play the first result"),
+            ("↑ / ↓", "Highlight a suggestion"),
+            ("Tab", "Accept suggestion / switch to results"),
+            ("Ctrl+R", "Re-run last search and jump to results"),
+            ("Ctrl+A / Ctrl+E", "Move to start / end of line"),
+            ("Ctrl+U / Ctrl+W", "Delete to start of line / previous word"),
+            ("Esc", "Switch to Global Mode"),
+        ],
+    },
+    Section {
+        title: "Search Results",
+        bindings: &[
+            ("Enter", "Play selected song"),
+            ("Tab", "Switch to the search bar"),
+            ("r", "Toggle keyword re-ranking"),
+            ("R", "Re-run last search"),
+            ("A", "More songs by the selected artist"),
+            ("D", "Download for offline playback"),
+            ("y", "Copy the song's YouTube URL"),
+            ("o", "Open the song in the browser"),
+            ("L", "Show / hide lyrics beside the search"),
+        ],
+    },
+    Section {
+        title: "History",
+        bindings: &[
+            ("Enter", "Play selected song"),
+            ("d", "Delete selected entry"),
+            ("A", "More songs by the selected artist"),
+            ("D", "Download for offline playback"),
+            ("y", "Copy the song's YouTube URL"),
+            ("o", "Open the song in the browser"),
+        ],
+    },
+    Section {
+        title: "Downloads",
+        bindings: &[
+            ("Enter", "Play selected song, offline when downloaded"),
+            ("d / Delete", "Delete the downloaded file"),
+        ],
+    },
+    Section {
+        title: "Local Music",
+        bindings: &[
+            ("Enter", "Play selected file"),
+            ("r", "Scan the folders again"),
+        ],
+    },
+    Section {
+        title: "Player",
+        bindings: &[
+            ("Space / ;", "Pause / resume current song"),
+            ("→ / l", "Skip forward 5 seconds"),
+            ("← / j", "Rewind 5 seconds"),
+            ("y", "Copy the song's YouTube URL"),
+            ("o", "Open the song in the browser"),
+            ("p / Esc", "Return to the previous view"),
+        ],
+    },
+];
+
+// Scrollable table of every key binding, grouped by the context it applies in
+pub struct Help {
+    nav: ListNav,      // Selection within the table rows
+    table: TableState, // Kept between frames so the scroll offset sticks
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Help {
+    pub fn new() -> Self {
+        let mut nav = ListNav::new();
+        nav.set_len(row_count());
+        Self {
+            nav,
+            table: TableState::default(),
+        }
+    }
+
+    // Scrolls to the heading of the named section, so help opens where it is relevant
+    pub fn show_section(&mut self, title: &str) {
+        let mut row = 0;
+        for section in SECTIONS {
+            if section.title == title {
+                self.nav.select(row);
+                *self.table.offset_mut() = row; // Heading at the top of the view
+                return;
+            }
+            row += section.bindings.len() + 1;
+        }
+    }
+
+    // Count typed before the next motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.nav.pending_count()
+    }
+
+    // Scrolls the table with the usual list keys, returning whether the key was used
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
+        self.nav.handle_key(key)
+    }
+
+    // Renders the table with a scrollbar on the right when it does not fit
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let section_style = accent_style().add_modifier(Modifier::BOLD);
+        let mut rows = Vec::with_capacity(row_count());
+        for section in SECTIONS {
+            rows.push(Row::new(vec![Cell::from(section.title)]).style(section_style));
+            for (key, action) in section.bindings {
+                rows.push(Row::new(vec![Cell::from(*key), Cell::from(*action)]));
+            }
+        }
+
+        self.nav.set_len(rows.len());
+        self.nav.set_page_height(area.height.saturating_sub(1)); // Header row
+        self.table.select(Some(self.nav.selected()));
+        StatefulWidget::render(
+            Table::new(
+                rows,
+                [Constraint::Percentage(30), Constraint::Percentage(70)],
+            )
+            .block(Block::default().borders(Borders::ALL).title("Help"))
+            .header(Row::new(vec![Cell::from("Key"), Cell::from("Action")]))
+            .row_highlight_style(selected_style()),
+            area,
+            buf,
+            &mut self.table,
+        );
+
+        self.nav.render_scrollbar(area, buf);
+    }
+}
+
+// Number of table rows, section titles included
+fn row_count() -> usize {
+    SECTIONS
+        .iter()
+        .map(|section| section.bindings.len() + 1)
+        .sum()
+}
@@ -1,25 +1,32 @@
-use crate::backend::{Backend, Song};
+use crate::list_nav::ListNav;
+use crate::player::spawn_play;
+use crate::redraw::Redraw;
+use crate::status_bar::{MESSAGE_DURATION, StatusBar};
+use crate::text::truncate_to_width;
+use crate::theme::{NOW_PLAYING, border_style, selected_style, text_style};
+use crate::{clipboard, downloads, opener};
 use crossterm::event::{KeyCode, KeyEvent};
-use feather::database::HistoryDB;
-use ratatui::prelude::{Buffer, Color, Constraint, Layout, Rect};
+use feather::ArtistName;
+use feather::backend::{Backend, Song};
+use feather::database::{HistoryDB, HistoryEntry};
+use ratatui::prelude::{Buffer, Constraint, Layout, Rect};
 use ratatui::style::Style;
 use ratatui::text::Span;
 use ratatui::widgets::{
-    Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarState,
-    StatefulWidget, Widget,
+    Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
 };
 use std::sync::Arc;
-use tokio::sync::mpsc;
 
 // Defines a struct to manage playback history UI
 pub struct History {
-    history: Arc<HistoryDB>,               // Database connection for history
-    selected: usize,                       // Index of currently selected item
-    vertical_scroll_state: ScrollbarState, // State for vertical scrollbar
-    max_len: usize,                        // Total number of history items
-    selected_song: Option<Song>,           // Currently selected song details
-    backend: Arc<Backend>,                 // Audio backend for playback
-    tx_player: mpsc::Sender<bool>,         // Channel to communicate with player
+    history: Arc<HistoryDB>,            // Database connection for history
+    entries: Option<Vec<HistoryEntry>>, // Entries as last read, None if reading failed
+    loaded: Option<u64>,                // History generation `entries` was read at
+    nav: ListNav,                       // Selection within the history list
+    selected_song: Option<Song>,        // Currently selected song details
+    backend: Arc<Backend>,              // Audio backend for playback
+    status: StatusBar,                  // Action confirmations and playback errors
+    redraw: Redraw,                     // Wakes the render loop when playback starts or fails
 }
 
 impl History {
@@ -27,69 +34,116 @@ impl History {
     pub fn new(
         history: Arc<HistoryDB>,
         backend: Arc<Backend>,
-        tx_player: mpsc::Sender<bool>,
+        status: StatusBar,
+        redraw: Redraw,
     ) -> Self {
         Self {
             history,
-            selected: 0,
-            vertical_scroll_state: ScrollbarState::default(),
-            max_len: 0,
+            entries: None,
+            loaded: None,
+            nav: ListNav::new(),
             selected_song: None,
             backend,
-            tx_player,
+            status,
+            redraw,
         }
     }
 
-    // Handles keyboard input for navigation and actions
-    pub fn handle_keystrokes(&mut self, key: KeyEvent) {
+    // Handles keyboard input for navigation and actions, returning whether the key was used
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
+        if self.nav.handle_key(key) {
+            return true; // List movement
+        }
         match key.code {
-            KeyCode::Char('j') | KeyCode::Down => {
-                // Move selection down
-                self.select_next();
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                // Move selection up
-                self.select_previous();
-            }
             KeyCode::Char('d') => {
                 // Delete selected entry
                 if let Some(song) = &self.selected_song {
-                    let _ = self.history.delete_entry(&song.song_id);
+                    match self.history.delete_entry(&song.song_id) {
+                        Ok(_) => self.status.set_message(
+                            format!("Removed \"{}\" from history", song.song_name),
+                            MESSAGE_DURATION,
+                        ),
+                        Err(e) => self
+                            .status
+                            .set_error(format!("Failed to delete entry: {}", e), MESSAGE_DURATION),
+                    }
+                }
+            }
+            KeyCode::Char('D') => {
+                // Save the selected song for offline playback
+                if let Some(song) = self.selected_song.clone() {
+                    downloads::start_download(
+                        Arc::clone(&self.backend),
+                        song,
+                        self.status.clone(),
+                        self.redraw.clone(),
+                    );
+                }
+            }
+            KeyCode::Char('y') => {
+                // Copy the selected song's URL
+                if let Some(song) = &self.selected_song {
+                    self.status
+                        .report(clipboard::copy(&song.url()), "Copied URL to clipboard");
+                }
+            }
+            KeyCode::Char('o') => {
+                // Open the selected song in the browser
+                if let Some(song) = &self.selected_song {
+                    self.status
+                        .report(opener::open_url(&song.url()), "Opened in browser");
                 }
             }
             KeyCode::Enter => {
                 // Play selected song
                 if let Some(song) = self.selected_song.clone() {
-                    let backend = Arc::clone(&self.backend);
-                    let tx_player = self.tx_player.clone();
-                    tokio::spawn(async move {
-                        // Spawn async task for playback
-                        if backend.play_music(song).await.is_ok() {
-                            let _ = tx_player.send(true).await;
-                        }
-                    });
+                    spawn_play(
+                        self.backend.clone(),
+                        song,
+                        self.status.clone(),
+                        self.redraw.clone(),
+                    );
                 }
             }
-            _ => (), // Ignore other keys
+            _ => return false, // Left to the global bindings
         }
+        true
     }
 
-    // Moves selection to next item, respecting bounds
-    fn select_next(&mut self) {
-        if self.max_len > 0 {
-            self.selected = (self.selected + 1).min(self.max_len - 1);
-            self.vertical_scroll_state = self.vertical_scroll_state.position(self.selected);
-        }
+    // Selects the history row under a left click, returning whether a row was hit
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        self.nav.click(column, row)
+    }
+
+    // Moves the selection with the mouse wheel
+    pub fn scroll(&mut self, down: bool) {
+        self.nav.scroll(down)
+    }
+
+    // Count typed before the next list motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.nav.pending_count()
     }
 
-    // Moves selection to previous item, preventing underflow
-    fn select_previous(&mut self) {
-        self.selected = self.selected.saturating_sub(1);
-        self.vertical_scroll_state = self.vertical_scroll_state.position(self.selected);
+    // Returns the first artist of the selected song, if any
+    pub fn selected_artist(&self) -> Option<ArtistName> {
+        self.selected_song
+            .as_ref()
+            .and_then(|song| song.artist_name.first().cloned())
     }
 
-    // Renders the history UI component
-    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+    // Reads the history again if it changed since the last read
+    fn refresh(&mut self) {
+        let generation = self.history.generation();
+        if self.loaded == Some(generation) {
+            return;
+        }
+        self.entries = self.history.get_history().ok();
+        self.loaded = Some(generation);
+    }
+
+    // Renders the history UI component, highlighting its borders when focused
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([Constraint::Length(3), Constraint::Min(0)]) // Split layout
@@ -97,28 +151,32 @@ impl History {
 
         // Render title bar
         Paragraph::new("History")
-            .style(Style::default().fg(Color::White))
-            .block(Block::default().borders(Borders::ALL))
+            .style(text_style())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style(focused)),
+            )
             .render(chunks[0], buf);
 
-        // Setup history list area with scrollbar
+        // Setup history list area
         let history_area = chunks[1];
-        let scrollbar = Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑"))
-            .end_symbol(Some("↓"));
-        scrollbar.render(history_area, buf, &mut self.vertical_scroll_state);
 
-        // Fetch and render history items
-        if let Ok(items) = self.history.get_history() {
-            self.max_len = items.len();
-            self.vertical_scroll_state = self.vertical_scroll_state.content_length(self.max_len);
+        // Render the history items, reading them only after a change
+        self.refresh();
+        if let Some(items) = &self.entries {
+            self.nav.set_len(items.len());
+            self.nav.set_page_height(history_area.height);
+            // Columns left for the text after borders and highlight symbol
+            let row_width = history_area.width.saturating_sub(3) as usize;
+            let playing = self.backend.current_song_id();
 
             let view_items: Vec<ListItem> = items
-                .into_iter()
+                .iter()
                 .enumerate()
                 .map(|(i, item)| {
                     // Format each item for display
-                    let is_selected = i == self.selected;
+                    let is_selected = i == self.nav.selected();
                     if is_selected {
                         self.selected_song = Some(Song::new(
                             item.song_name.clone(),
@@ -128,30 +186,45 @@ impl History {
                     }
                     let style = if is_selected {
                         // Highlight selected item
-                        Style::default().fg(Color::Yellow).bg(Color::Blue)
+                        selected_style()
                     } else {
                         Style::default()
                     };
-                    let text = format!("{} - {}", item.song_name, item.artist_name.join(", "));
-                    ListItem::new(Span::styled(text, style))
+                    let marker = if playing.as_ref() == Some(&item.song_id) {
+                        NOW_PLAYING
+                    } else {
+                        ""
+                    };
+                    let text = format!(
+                        "{}{} - {}",
+                        marker,
+                        item.song_name,
+                        item.artist_name.join(", ")
+                    );
+                    ListItem::new(Span::styled(truncate_to_width(&text, row_width), style))
                 })
                 .collect();
 
             let mut list_state = ListState::default();
-            list_state.select(Some(self.selected));
+            list_state.select(Some(self.nav.selected()));
             StatefulWidget::render(
                 // Render the list
                 List::new(view_items)
-                    .block(Block::default().borders(Borders::ALL))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(border_style(focused)),
+                    )
                     .highlight_symbol("▶"),
                 history_area,
                 buf,
                 &mut list_state,
             );
+            self.nav.set_area(history_area, list_state.offset());
+            self.nav.render_scrollbar(history_area, buf);
         } else {
             // Handle history loading failure
-            self.max_len = 0;
-            self.selected = 0;
+            self.nav.set_len(0);
             Paragraph::new("Failed to load history").render(history_area, buf);
         }
     }
@@ -0,0 +1,77 @@
+use crate::debug_log;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const LOCK_FILE: &str = "feather.lock"; // In the data directory, next to the databases
+
+// Keeps a second Feather off the same data directory, whose databases only one process
+// may open. The lock is held on a file holding our PID; the OS releases it when the
+// process ends, however it ends, so a crash cannot leave Feather locked out.
+pub struct InstanceLock {
+    file: Option<File>, // Locked until dropped, None when the lock file cannot be opened
+}
+
+impl InstanceLock {
+    // Takes the lock, or returns the PID of the instance holding it, if known
+    pub fn acquire(data_dir: &Path) -> Result<Self, Option<u32>> {
+        // Failing to create the lock is not worth refusing to start over
+        let opened = fs::create_dir_all(data_dir).and_then(|_| {
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .read(true)
+                .write(true)
+                .open(data_dir.join(LOCK_FILE))
+        });
+        let mut file = match opened {
+            Ok(file) => file,
+            Err(e) => {
+                debug_log::log(&format!("instance lock: {}", e));
+                return Ok(Self { file: None });
+            }
+        };
+        match file.try_lock() {
+            Ok(()) => (),
+            Err(TryLockError::WouldBlock) => return Err(read_pid(&mut file)),
+            Err(TryLockError::Error(e)) => {
+                debug_log::log(&format!("instance lock: {}", e));
+                return Ok(Self { file: None });
+            }
+        }
+        // A clean exit empties the file, so a PID left in it belongs to a crashed instance
+        if let Some(pid) = read_pid(&mut file) {
+            debug_log::log(&format!(
+                "instance lock: cleaned up after instance {} that did not exit cleanly",
+                pid
+            ));
+        }
+        let written = file
+            .set_len(0)
+            .and_then(|_| file.seek(SeekFrom::Start(0)))
+            .and_then(|_| write!(file, "{}", std::process::id()))
+            .and_then(|_| file.flush());
+        if let Err(e) = written {
+            debug_log::log(&format!("instance lock: {}", e));
+        }
+        Ok(Self { file: Some(file) })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The file stays, emptied, so whoever opens it next locks the same file
+        if let Some(file) = &self.file {
+            let _ = file.set_len(0);
+            let _ = file.unlock();
+        }
+    }
+}
+
+// Reads the PID written by the instance holding the lock
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
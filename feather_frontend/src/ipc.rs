@@ -0,0 +1,176 @@
+use crate::cli::best_match;
+use crate::debug_log;
+use feather::backend::Backend;
+use serde_json::json;
+use std::env;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+
+const VOLUME_STEP: u8 = 5; // Change for a bare "volume +" or "volume -"
+
+// Commands understood by the socket, one per line
+pub const COMMANDS: &str =
+    "play-pause, play [query], pause, stop, next, prev, volume [+|-]<n>, status";
+
+// Socket clients talk to, $XDG_RUNTIME_DIR/feather.sock, or one per user in /tmp
+pub fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("feather.sock"),
+        None => {
+            let user = env::var("USER").unwrap_or_else(|_| "feather".to_string());
+            env::temp_dir().join(format!("feather-{}.sock", user))
+        }
+    }
+}
+
+// Sends one command to a running Feather and returns its reply line
+pub fn send(command: &str) -> std::io::Result<String> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket_path())?;
+    writeln!(stream, "{}", command)?;
+    let mut reply = String::new();
+    std::io::BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
+
+// Remote control over a Unix socket, enabled with FEATHER_IPC=1.
+// Every connection may send any number of commands and gets one reply line per command:
+// "ok", "error: <reason>", or the JSON status.
+pub struct IpcServer {
+    path: PathBuf,        // Removed again when the server stops
+    task: JoinHandle<()>, // Accepts connections until the app exits
+}
+
+impl IpcServer {
+    pub fn start(backend: Arc<Backend>) -> Option<Self> {
+        if env::var("FEATHER_IPC").as_deref() != Ok("1") {
+            return None;
+        }
+        let path = socket_path();
+        if path.exists() {
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                debug_log::log("ipc: another Feather already listens on the socket");
+                return None;
+            }
+            let _ = std::fs::remove_file(&path); // Left behind by a crashed instance
+        }
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                debug_log::log(&format!("ipc: cannot bind {}: {}", path.display(), e));
+                return None;
+            }
+        };
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, Arc::clone(&backend)));
+            }
+        });
+        Some(Self { path, task })
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Answers the commands of one client until it disconnects
+async fn serve(stream: UnixStream, backend: Arc<Backend>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = match execute(line.trim(), &backend).await {
+            Ok(reply) => reply,
+            Err(e) => format!("error: {}", e),
+        };
+        if writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+// Runs one command against the player
+async fn execute(command: &str, backend: &Backend) -> Result<String, String> {
+    let player = &backend.player;
+    let mut words = command.split_whitespace();
+    let result = match (words.next(), words.next()) {
+        (Some("play-pause"), None) => player.play_pause(),
+        (Some("play"), None) => player.unpause(),
+        (Some("play"), Some(_)) => {
+            // Everything after "play" is the query, as with `feather play`
+            let query = command["play".len()..].trim();
+            let song = best_match(backend, query).await?;
+            let title = song.song_name.clone();
+            backend.play_music(song).await.map_err(|e| e.to_string())?;
+            return Ok(format!("playing {}", title));
+        }
+        (Some("pause") | Some("stop"), None) => player.pause(),
+        (Some("prev"), None) => player.seek_to(0.0), // Restarts the song
+        (Some("next"), None) => return Err("there is no queue to skip in".to_string()),
+        (Some("volume"), Some(change)) => {
+            let volume = player.volume().map_err(|e| e.to_string())?;
+            player.set_volume(new_volume(volume, change)?)
+        }
+        (Some("volume"), None) => {
+            return player
+                .volume()
+                .map(|volume| volume.to_string())
+                .map_err(|e| e.to_string());
+        }
+        (Some("status"), None) => return Ok(status(backend)),
+        _ => return Err(format!("unknown command, expected one of: {}", COMMANDS)),
+    };
+    result.map(|_| "ok".to_string()).map_err(|e| e.to_string())
+}
+
+// Applies "+", "-", "+10", "-10" or an absolute "40" to the current volume
+fn new_volume(volume: u8, change: &str) -> Result<u8, String> {
+    let amount = |digits: &str| {
+        if digits.is_empty() {
+            Ok(VOLUME_STEP)
+        } else {
+            digits
+                .parse::<u8>()
+                .map_err(|_| format!("invalid volume: {}", change))
+        }
+    };
+    match change.as_bytes().first() {
+        Some(b'+') => Ok(volume.saturating_add(amount(&change[1..])?).min(100)),
+        Some(b'-') => Ok(volume.saturating_sub(amount(&change[1..])?)),
+        _ => change
+            .parse::<u8>()
+            .map(|volume| volume.min(100))
+            .map_err(|_| format!("invalid volume: {}", change)),
+    }
+}
+
+// Describes the current song and playback state as one line of JSON
+fn status(backend: &Backend) -> String {
+    let song = backend.song.lock().ok().and_then(|song| song.clone());
+    let state = match (&song, backend.player.is_playing()) {
+        (Some(_), Ok(true)) => "playing",
+        (Some(_), Ok(false)) => "paused",
+        _ => "stopped",
+    };
+    json!({
+        "state": state,
+        "title": song.as_ref().map(|s| s.song_name.clone()),
+        "artists": song.as_ref().map(|s| s.artist_name.clone()),
+        "id": song.as_ref().map(|s| s.song_id.clone()),
+        "url": song.as_ref().map(|s| s.url()),
+        "position": backend.player.position_secs(),
+        "duration": backend.player.duration_secs(),
+        "volume": backend.player.volume().ok(),
+    })
+    .to_string()
+}
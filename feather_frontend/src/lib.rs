@@ -1,4 +1,33 @@
-pub mod backend;
+// As in the feather crate, mutex guards never live across an await
+#![deny(clippy::await_holding_lock)]
+
+pub mod art;
+pub mod backup;
+pub mod cli;
+pub mod clipboard;
+pub mod debug_log;
+#[cfg(feature = "discord")]
+pub mod discord;
+pub mod downloads;
+pub mod help;
 pub mod history;
+pub mod instance;
+#[cfg(unix)]
+pub mod ipc;
+pub mod list_nav;
+pub mod local;
+pub mod lyrics;
+#[cfg(feature = "mpris")]
+pub mod mpris;
+pub mod now_playing;
+pub mod opener;
 pub mod player;
+pub mod redraw;
+pub mod scrobble;
 pub mod search;
+pub mod sponsorblock;
+pub mod status_bar;
+#[cfg(unix)]
+pub mod suspend;
+pub mod text;
+pub mod theme;
@@ -0,0 +1,304 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Position, Rect};
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget};
+use std::env;
+
+const DEFAULT_PAGE: usize = 10; // Rows per page until the list has been rendered
+
+// Selection and scrollbar bookkeeping shared by every list widget
+pub struct ListNav {
+    selected: usize,              // Index of the selected item
+    len: usize,                   // Number of items in the list
+    wrap: bool,                   // Whether moving past either end wraps around
+    page: usize,                  // Rows visible in the last rendered area
+    area: Rect,                   // Area the list was last rendered into
+    offset: usize,                // Index of the first visible item in that area
+    scroll_state: ScrollbarState, // Vertical scrollbar state
+    count: Option<usize>,         // Count typed before the next motion, as in `5j`
+}
+
+impl Default for ListNav {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListNav {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            len: 0,
+            wrap: env::var("FEATHER_WRAP_NAVIGATION").as_deref() == Ok("1"),
+            page: DEFAULT_PAGE,
+            area: Rect::default(),
+            offset: 0,
+            scroll_state: ScrollbarState::default(),
+            count: None,
+        }
+    }
+
+    // Count typed so far for the next motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    // Index of the selected item
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    // Updates the item count, keeping the selection inside the list
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.selected = self.selected.min(len.saturating_sub(1));
+        self.sync_scroll();
+    }
+
+    // Records how many rows fit in the rendered list area, borders excluded
+    pub fn set_page_height(&mut self, height: u16) {
+        self.page = (height.saturating_sub(2) as usize).max(1);
+        self.sync_scroll();
+    }
+
+    // Records where the list was rendered and how far it was scrolled, for mouse hit-testing
+    pub fn set_area(&mut self, area: Rect, offset: usize) {
+        self.area = area;
+        self.offset = offset;
+    }
+
+    // Returns the index of the item drawn at the given terminal cell, if any
+    pub fn index_at(&self, column: u16, row: u16) -> Option<usize> {
+        let inner = Rect {
+            x: self.area.x.saturating_add(1),
+            y: self.area.y.saturating_add(1),
+            width: self.area.width.saturating_sub(2),
+            height: self.area.height.saturating_sub(2),
+        };
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        let index = self.offset + (row - inner.y) as usize;
+        (index < self.len).then_some(index)
+    }
+
+    // Selects the item drawn at a left-clicked cell, returning whether an item was hit
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        match self.index_at(column, row) {
+            Some(index) => {
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Moves the selection one item with the mouse wheel
+    pub fn scroll(&mut self, down: bool) {
+        if down {
+            self.next();
+        } else {
+            self.previous();
+        }
+    }
+
+    // Selects the item at the given index, if it exists
+    pub fn select(&mut self, index: usize) {
+        if index < self.len {
+            self.selected = index;
+            self.sync_scroll();
+        }
+    }
+
+    // Handles the movement keys shared by all lists, returning whether the key was used
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        // Digits build up a count for the next motion, a leading 0 is not a count
+        if let KeyCode::Char(c @ '0'..='9') = key.code
+            && !ctrl
+            && (c != '0' || self.count.is_some())
+        {
+            let digit = c as usize - '0' as usize;
+            self.count = Some(
+                self.count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            return true;
+        }
+        let count = self.count.take(); // Any other key ends the count
+        match key.code {
+            KeyCode::Char('d') if ctrl => self.move_down((self.page / 2).max(1)),
+            KeyCode::Char('u') if ctrl => self.move_up((self.page / 2).max(1)),
+            KeyCode::PageDown => self.move_down(self.page),
+            KeyCode::PageUp => self.move_up(self.page),
+            KeyCode::Char('j') | KeyCode::Down => match count {
+                Some(rows) => self.move_down(rows),
+                None => self.next(),
+            },
+            KeyCode::Char('k') | KeyCode::Up => match count {
+                Some(rows) => self.move_up(rows),
+                None => self.previous(),
+            },
+            // With a count both jump to that item, counting from 1
+            KeyCode::Char('g') | KeyCode::Char('G') | KeyCode::Home | KeyCode::End
+                if count.is_some() =>
+            {
+                self.first();
+                self.move_down(count.unwrap_or(1).saturating_sub(1));
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.first(),
+            KeyCode::Char('G') | KeyCode::End => self.last(),
+            _ => return false,
+        }
+        true
+    }
+
+    // Moves selection to next item, wrapping to the top if enabled
+    pub fn next(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        if self.selected + 1 < self.len {
+            self.selected += 1;
+        } else if self.wrap {
+            self.selected = 0;
+        }
+        self.sync_scroll();
+    }
+
+    // Moves selection to previous item, wrapping to the bottom if enabled
+    pub fn previous(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else if self.wrap {
+            self.selected = self.len - 1;
+        }
+        self.sync_scroll();
+    }
+
+    // Moves the selection down by several rows, stopping at the last item
+    pub fn move_down(&mut self, rows: usize) {
+        self.selected = (self.selected + rows).min(self.len.saturating_sub(1));
+        self.sync_scroll();
+    }
+
+    // Moves the selection up by several rows, stopping at the first item
+    pub fn move_up(&mut self, rows: usize) {
+        self.selected = self.selected.saturating_sub(rows);
+        self.sync_scroll();
+    }
+
+    // Jumps to the first item
+    pub fn first(&mut self) {
+        self.selected = 0;
+        self.sync_scroll();
+    }
+
+    // Jumps to the last item
+    pub fn last(&mut self) {
+        self.selected = self.len.saturating_sub(1);
+        self.sync_scroll();
+    }
+
+    // Draws the scrollbar on the right border of the list, unless every item already fits
+    pub fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer) {
+        if self.len <= self.page {
+            return;
+        }
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓"))
+            .render(area, buf, &mut self.scroll_state);
+    }
+
+    // Keeps the scrollbar in step with the list size and selection
+    fn sync_scroll(&mut self) {
+        self.scroll_state = self
+            .scroll_state
+            .content_length(self.len)
+            .viewport_content_length(self.page)
+            .position(self.selected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nav(len: usize, wrap: bool) -> ListNav {
+        let mut nav = ListNav {
+            wrap,
+            ..ListNav::new()
+        };
+        nav.set_len(len);
+        nav
+    }
+
+    #[test]
+    fn empty_list_keeps_the_selection_at_zero() {
+        let mut nav = nav(0, true);
+        nav.next();
+        nav.previous();
+        nav.move_down(3);
+        nav.last();
+        assert_eq!(nav.selected(), 0);
+        nav.set_area(Rect::new(0, 0, 20, 6), 0);
+        assert_eq!(nav.index_at(1, 1), None);
+        assert!(!nav.click(1, 1));
+    }
+
+    #[test]
+    fn shrinking_clamps_the_selection() {
+        let mut nav = nav(10, false);
+        nav.select(8);
+        nav.set_len(3);
+        assert_eq!(nav.selected(), 2);
+        nav.next();
+        assert_eq!(nav.selected(), 2); // Stays on the last item without wrapping
+        nav.set_len(0);
+        assert_eq!(nav.selected(), 0);
+    }
+
+    #[test]
+    fn wrapping_follows_the_new_length() {
+        let mut nav = nav(10, true);
+        nav.select(9);
+        nav.set_len(5);
+        assert_eq!(nav.selected(), 4);
+        nav.next();
+        assert_eq!(nav.selected(), 0);
+        nav.previous();
+        assert_eq!(nav.selected(), 4);
+    }
+
+    #[test]
+    fn without_wrapping_the_ends_hold() {
+        let mut nav = nav(3, false);
+        nav.previous();
+        assert_eq!(nav.selected(), 0);
+        nav.last();
+        nav.next();
+        assert_eq!(nav.selected(), 2);
+    }
+
+    #[test]
+    fn index_at_maps_rows_inside_the_borders() {
+        let mut nav = nav(5, false);
+        nav.set_area(Rect::new(2, 1, 20, 6), 3); // Rows 2 to 5 show items from index 3 on
+        assert_eq!(nav.index_at(3, 2), Some(3));
+        assert_eq!(nav.index_at(20, 3), Some(4));
+        assert_eq!(nav.index_at(3, 4), None); // Past the last item
+        assert_eq!(nav.index_at(2, 2), None); // Left border
+        assert_eq!(nav.index_at(21, 2), None); // Right border
+        assert_eq!(nav.index_at(3, 1), None); // Top border
+        assert_eq!(nav.index_at(3, 7), None); // Below the list
+
+        assert!(nav.click(3, 3));
+        assert_eq!(nav.selected(), 4);
+    }
+}
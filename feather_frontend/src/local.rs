@@ -0,0 +1,263 @@
+use crate::list_nav::ListNav;
+use crate::player::spawn_play;
+use crate::redraw::Redraw;
+use crate::status_bar::StatusBar;
+use crate::text::truncate_to_width;
+use crate::theme::{NOW_PLAYING, border_style, selected_style, text_style};
+use crossterm::event::{KeyCode, KeyEvent};
+use feather::backend::{Backend, Song};
+use ratatui::prelude::{Buffer, Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
+use ratatui::widgets::{
+    Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// File extensions mpv is asked to play from the local music folders
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "aac", "aiff", "alac", "flac", "m4a", "mka", "mp3", "oga", "ogg", "opus", "wav", "webm", "wma",
+];
+
+// Folders listed in FEATHER_LOCAL_MUSIC_DIRS, separated like PATH
+pub fn music_dirs() -> Vec<PathBuf> {
+    env::var_os("FEATHER_LOCAL_MUSIC_DIRS")
+        .map(|dirs| {
+            env::split_paths(&dirs)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Walks the folders recursively and returns their audio files as songs, sorted by artist and title.
+// Names come from "Artist - Title.ext" file names, or from the file name and its folder.
+pub fn scan(dirs: &[PathBuf]) -> Vec<Song> {
+    let mut songs = Vec::new();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue; // Unreadable folders are skipped
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if is_audio(&path)
+                && let Some(song) = song_from_path(&path)
+            {
+                songs.push(song);
+            }
+        }
+    }
+    songs.sort_by_cached_key(|song| {
+        (
+            song.artist_name.join(", ").to_lowercase(),
+            song.song_name.to_lowercase(),
+        )
+    });
+    songs
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| AUDIO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+// Builds a song whose ID is the absolute path, so history and playback know it is local
+fn song_from_path(path: &Path) -> Option<Song> {
+    let path = path.canonicalize().ok()?;
+    let stem = path.file_stem()?.to_string_lossy().into_owned();
+    let (artist, title) = match stem.split_once(" - ") {
+        Some((artist, title)) => (artist.trim().to_string(), title.trim().to_string()),
+        None => {
+            let folder = path
+                .parent()
+                .and_then(|parent| parent.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (folder, stem)
+        }
+    };
+    let artists = if artist.is_empty() {
+        Vec::new()
+    } else {
+        vec![artist]
+    };
+    Some(Song::new(
+        title,
+        path.to_string_lossy().into_owned(),
+        artists,
+    ))
+}
+
+// Lists the songs found in the local music folders; Enter plays, r rescans
+pub struct LocalView {
+    nav: ListNav,                         // Selection within the list
+    songs: Arc<Mutex<Option<Vec<Song>>>>, // Scan result, `None` while scanning
+    dirs: Vec<PathBuf>,                   // Folders being scanned
+    selected_song: Option<Song>,          // Song under the cursor
+    backend: Arc<Backend>,                // Audio backend for playback
+    status: StatusBar,                    // Playback errors
+    redraw: Redraw,                       // Wakes the render loop when a scan finishes
+}
+
+impl LocalView {
+    pub fn new(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Self {
+        let view = Self {
+            nav: ListNav::new(),
+            songs: Arc::new(Mutex::new(None)),
+            dirs: music_dirs(),
+            selected_song: None,
+            backend,
+            status,
+            redraw,
+        };
+        view.rescan();
+        view
+    }
+
+    // Scans the folders again in the background, for files added while Feather runs
+    fn rescan(&self) {
+        if let Ok(mut songs) = self.songs.lock() {
+            *songs = None;
+        }
+        let dirs = self.dirs.clone();
+        let songs = Arc::clone(&self.songs);
+        let redraw = self.redraw.clone();
+        tokio::task::spawn_blocking(move || {
+            let found = scan(&dirs);
+            if let Ok(mut songs) = songs.lock() {
+                *songs = Some(found);
+            }
+            redraw.request();
+        });
+    }
+
+    // Selects the row under a left click, returning whether a row was hit
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        self.nav.click(column, row)
+    }
+
+    // Moves the selection with the mouse wheel
+    pub fn scroll(&mut self, down: bool) {
+        self.nav.scroll(down)
+    }
+
+    // Count typed before the next list motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.nav.pending_count()
+    }
+
+    // Handles keyboard input for navigation and actions, returning whether the key was used
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
+        if self.nav.handle_key(key) {
+            return true; // List movement
+        }
+        match key.code {
+            KeyCode::Enter => {
+                // Play the selected file
+                if let Some(song) = self.selected_song.clone() {
+                    spawn_play(
+                        self.backend.clone(),
+                        song,
+                        self.status.clone(),
+                        self.redraw.clone(),
+                    );
+                }
+            }
+            KeyCode::Char('r') => self.rescan(),
+            _ => return false, // Left to the global bindings
+        }
+        true
+    }
+
+    // Renders the list of local songs, highlighting its borders when focused
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+
+        Paragraph::new("Local Music")
+            .style(text_style())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style(focused)),
+            )
+            .render(chunks[0], buf);
+
+        let list_area = chunks[1];
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style(focused));
+        // Rendered under the lock, large libraries are not copied every frame
+        let guard = self.songs.lock();
+        let songs = match guard.as_deref() {
+            _ if self.dirs.is_empty() => Err("Set FEATHER_LOCAL_MUSIC_DIRS to browse local files"),
+            Ok(None) => Err("Scanning…"),
+            Ok(Some(songs)) if songs.is_empty() => Err("No audio files found"),
+            Ok(Some(songs)) => Ok(songs),
+            Err(_) => Err("Failed to load local music"),
+        };
+        let songs = match songs {
+            Ok(songs) => songs,
+            Err(message) => {
+                self.nav.set_len(0);
+                self.selected_song = None;
+                Paragraph::new(message).block(block).render(list_area, buf);
+                return;
+            }
+        };
+
+        self.nav.set_len(songs.len());
+        self.nav.set_page_height(list_area.height);
+        self.selected_song = songs.get(self.nav.selected()).cloned();
+        // Columns left for the text after borders and highlight symbol
+        let row_width = list_area.width.saturating_sub(3) as usize;
+        let playing = self.backend.current_song_id();
+
+        let items: Vec<ListItem> = songs
+            .iter()
+            .enumerate()
+            .map(|(i, song)| {
+                let style = if i == self.nav.selected() {
+                    selected_style()
+                } else {
+                    Style::default()
+                };
+                let marker = if playing.as_ref() == Some(&song.song_id) {
+                    NOW_PLAYING
+                } else {
+                    ""
+                };
+                let text = format!(
+                    "{}{} - {}",
+                    marker,
+                    song.song_name,
+                    song.artist_name.join(", ")
+                );
+                ListItem::new(Span::styled(truncate_to_width(&text, row_width), style))
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        list_state.select(Some(self.nav.selected()));
+        StatefulWidget::render(
+            List::new(items).block(block).highlight_symbol("▶"),
+            list_area,
+            buf,
+            &mut list_state,
+        );
+        self.nav.set_area(list_area, list_state.offset());
+        self.nav.render_scrollbar(list_area, buf);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::redraw::Redraw;
+use crate::theme::{accent_style, border_style, muted_style};
+use feather::SongId;
+use feather::backend::{Backend, Song};
+use feather::database::Lyrics;
+use ratatui::prelude::{Buffer, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Widget, Wrap};
+use std::sync::{Arc, Mutex};
+
+// What the pane shows for the current song
+enum Loaded {
+    Loading,
+    Done(Lyrics),
+    Failed(String),
+}
+
+// Lyrics of the current song, following the playhead when they are synced
+pub struct LyricsView {
+    backend: Arc<Backend>, // Current song, lyrics lookup and playhead
+    loaded: Arc<Mutex<Option<(SongId, Loaded)>>>, // Lookup result for the song it was made for
+    redraw: Redraw,        // Wakes the render loop when a lookup finishes
+}
+
+impl LyricsView {
+    pub fn new(backend: Arc<Backend>, redraw: Redraw) -> Self {
+        Self {
+            backend,
+            loaded: Arc::new(Mutex::new(None)),
+            redraw,
+        }
+    }
+
+    // Starts a lookup when the song changed since the last one
+    fn refresh(&self, song: &Song) {
+        let Ok(mut loaded) = self.loaded.lock() else {
+            return;
+        };
+        if loaded.as_ref().is_some_and(|(id, _)| *id == song.song_id) {
+            return;
+        }
+        *loaded = Some((song.song_id.clone(), Loaded::Loading));
+        let backend = Arc::clone(&self.backend);
+        let shared = Arc::clone(&self.loaded);
+        let redraw = self.redraw.clone();
+        let song = song.clone();
+        tokio::spawn(async move {
+            let result = match backend.lyrics(&song).await {
+                Ok(lyrics) => Loaded::Done(lyrics),
+                Err(e) => Loaded::Failed(e.to_string()),
+            };
+            if let Ok(mut loaded) = shared.lock() {
+                // A newer song may have started meanwhile
+                if loaded.as_ref().is_some_and(|(id, _)| *id == song.song_id) {
+                    *loaded = Some((song.song_id, result));
+                }
+            }
+            redraw.request();
+        });
+    }
+
+    // Renders the lyrics, keeping the line being sung in the middle of the pane
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        let song = self.backend.song.lock().ok().and_then(|song| song.clone());
+        let title = match &song {
+            Some(song) => format!("Lyrics — {}", song.song_name),
+            None => "Lyrics".to_string(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style(focused))
+            .title(title);
+        let Some(song) = song else {
+            Paragraph::new("Nothing is playing")
+                .block(block)
+                .render(area, buf);
+            return;
+        };
+        self.refresh(&song);
+
+        let Ok(loaded) = self.loaded.lock() else {
+            return;
+        };
+        let message = match loaded.as_ref().map(|(_, loaded)| loaded) {
+            Some(Loaded::Done(Lyrics::Synced(lines))) => {
+                let position = self.backend.player.position_secs();
+                let current = lines
+                    .iter()
+                    .rposition(|(time, _)| *time <= position)
+                    .unwrap_or(0);
+                let text: Vec<Line> = lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, line))| {
+                        let style = if i == current {
+                            accent_style()
+                        } else {
+                            muted_style()
+                        };
+                        Line::styled(line.as_str(), style)
+                    })
+                    .collect();
+                let middle = area.height.saturating_sub(2) / 2;
+                let scroll = (current as u16).saturating_sub(middle);
+                Paragraph::new(text)
+                    .block(block)
+                    .scroll((scroll, 0))
+                    .render(area, buf);
+                return;
+            }
+            Some(Loaded::Done(Lyrics::Plain(text))) => {
+                Paragraph::new(text.as_str())
+                    .block(block)
+                    .wrap(Wrap { trim: false })
+                    .render(area, buf);
+                return;
+            }
+            Some(Loaded::Done(Lyrics::Missing)) => "No lyrics found".to_string(),
+            Some(Loaded::Failed(e)) => format!("No lyrics found ({})", e),
+            Some(Loaded::Loading) | None => "Looking up lyrics…".to_string(),
+        };
+        Paragraph::new(message)
+            .style(muted_style())
+            .block(block)
+            .render(area, buf);
+    }
+}
@@ -1,221 +1,1030 @@
+#![deny(clippy::await_holding_lock)] // See lib.rs
+
 use color_eyre::eyre::Result;
-use crossterm::event::{Event, KeyCode, KeyEvent, poll, read};
-use feather::database::HistoryDB;
-use feather_frontend::{backend::Backend, history::History, player::SongPlayer, search::Search};
+use crossterm::{
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind, read,
+    },
+    execute,
+};
+use feather::{
+    backend::{Backend, BackendError},
+    cookies::CookieSource,
+    database::HistoryDB,
+    paths::Paths,
+    proxy::Proxy,
+};
+#[cfg(feature = "discord")]
+use feather_frontend::discord::DiscordPresence;
+#[cfg(feature = "mpris")]
+use feather_frontend::mpris::Mpris;
+use feather_frontend::{
+    backup::AutoBackup,
+    cli::{self, Command},
+    debug_log::{self, FrameTimer},
+    downloads::DownloadsView,
+    help::Help,
+    history::History,
+    instance::InstanceLock,
+    local::LocalView,
+    lyrics::LyricsView,
+    now_playing::NowPlayingFile,
+    player::{COMPACT_WIDTH, SongPlayer},
+    redraw::Redraw,
+    scrobble::Scrobbler,
+    search::Search,
+    sponsorblock::SponsorBlock,
+    status_bar::{ERROR_DURATION, MESSAGE_DURATION, StatusBar},
+    theme::accent_style,
+};
+#[cfg(unix)]
+use feather_frontend::{
+    ipc::{self, IpcServer},
+    suspend::PauseOnSuspend,
+};
 use ratatui::{
-    DefaultTerminal,
+    DefaultTerminal, Frame,
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Widget},
+    layout::{Alignment, Constraint, Layout, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use std::{
+    env,
+    io::{Write, stdout},
+    path::PathBuf,
+    sync::Arc,
+    time::Instant,
 };
-use std::{env, sync::Arc};
 use tokio::{
     sync::mpsc,
     time::{Duration, interval},
 };
 
+const SPLIT_STEP: u16 = 5; // Percent moved per `<` / `>` press
+const MIN_SPLIT: u16 = 20; // Narrowest either pane may get, in percent
+const MAX_SPLIT: u16 = 100 - MIN_SPLIT;
+const TICK: Duration = Duration::from_secs(1); // Redraw interval for the player clock
+const COMPACT_HEIGHT: u16 = 25; // Rows below which the chrome shrinks to make room for lists
+const DOUBLE_CLICK: Duration = Duration::from_millis(400); // Max gap between double-click halves
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1); // How long background tasks get to stop on exit (debug builds)
+
+const USAGE: &str = "Usage: feather_frontend [options] [command]
+
+Commands (run without the TUI):
+  play <query>       Play the first search result until it ends or Ctrl+C
+  history            Print the play history, most recent first
+  export <file>      Save the history to a file, e.g. to move it to another machine
+  import <file>      Merge a file written by export into the history
+  stats [--db]       Print the database's size, entries per tree and last flush
+  compact            Flush the database and print its size before and after
+
+Options:
+  --data-dir <path>  Directory for the history database and caches
+  --cookies <file>   Netscape cookies.txt for YouTube (falls back to FEATHER_COOKIES)
+  --cookies-from-browser <browser>
+                     Read YouTube cookies from the browser, only firefox for now
+  --incognito        Keep songs played by a command out of the history
+  -h, --help         Print this help";
+
 /// Entry point for the async runtime.
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install().unwrap();
-    let terminal = ratatui::init();
-    let _app = App::new().render(terminal).await;
+    // Parse arguments before raw mode so errors print to a usable terminal
+    let mut args = match Args::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    // Checked before any HTTP client is built, so a typo is reported like a bad argument
+    if let Err(e) = Proxy::from_env() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    // The databases allow one process per data directory, so a second launch stops here
+    let paths = args.data_dir.clone().map(Paths::new).unwrap_or_default();
+    let lock = match InstanceLock::acquire(&paths.data_dir) {
+        Ok(lock) => lock,
+        Err(pid) => std::process::exit(hand_over(args.command.take(), pid)),
+    };
+    if let Some(command) = args.command.take() {
+        let cookies = args.cookie_source();
+        if let Err(e) = cli::run(command, &paths, cookies, args.incognito).await {
+            drop(lock); // `exit` skips destructors
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // Opened before the terminal is taken over, so a failure prints like any other error
+    let history = match HistoryDB::new_at(&paths.data_dir) {
+        Ok(history) => Arc::new(history),
+        Err(e) => {
+            drop(lock);
+            eprintln!("Failed to open history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let backend = match Backend::new(history.clone(), &paths, args.cookie_source()) {
+        Ok(backend) => Arc::new(backend),
+        Err(e) => {
+            drop(lock);
+            eprintln!("Failed to start the player: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let terminal = ratatui::init(); // Also restores the terminal before a panic is reported
+    // Mouse capture disables the terminal's own text selection, so it is opt-in
+    let mouse = env::var("FEATHER_MOUSE").as_deref() == Ok("1");
+    if mouse {
+        let _ = execute!(stdout(), EnableMouseCapture);
+        // Release the mouse before the restoring hook runs, or the shell keeps receiving clicks
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = execute!(stdout(), DisableMouseCapture);
+            hook(info);
+        }));
+    }
+    // Save the terminal's own title so the one set while playing can be undone on exit
+    let terminal_title = env::var("FEATHER_TERMINAL_TITLE").as_deref() == Ok("1");
+    if terminal_title {
+        let _ = write!(stdout(), "\x1b[22;0t"); // XTWINOPS: push title
+    }
+    let _app = App::new(args, history, backend).render(terminal).await;
+    #[cfg(debug_assertions)]
+    report_leftover_tasks().await;
+    if mouse {
+        let _ = execute!(stdout(), DisableMouseCapture);
+    }
+    if terminal_title {
+        let _ = write!(stdout(), "\x1b[23;0t"); // XTWINOPS: pop title
+        let _ = stdout().flush();
+    }
     ratatui::restore();
     Ok(())
 }
 
+/// Handles a launch while another instance runs: `play` is forwarded to it over IPC,
+/// anything else only reports it. Returns the exit code.
+#[cfg(unix)]
+fn hand_over(command: Option<Command>, pid: Option<u32>) -> i32 {
+    let running = match pid {
+        Some(pid) => format!("Feather already running (pid {})", pid),
+        None => "Feather already running".to_string(),
+    };
+    let Some(Command::Play(query)) = command else {
+        eprintln!("{}", running);
+        return 1;
+    };
+    match ipc::send(&format!("play {}", query)) {
+        Ok(reply) => match reply.strip_prefix("error: ") {
+            Some(error) => {
+                eprintln!("{}", error);
+                1
+            }
+            None => {
+                println!("{}", reply);
+                0
+            }
+        },
+        Err(_) => {
+            eprintln!("{}; run it with FEATHER_IPC=1 to pass it songs", running);
+            1
+        }
+    }
+}
+
+/// Without IPC there is nothing to hand a song to, so the running instance is only reported.
+#[cfg(not(unix))]
+fn hand_over(_command: Option<Command>, pid: Option<u32>) -> i32 {
+    match pid {
+        Some(pid) => eprintln!("Feather already running (pid {})", pid),
+        None => eprintln!("Feather already running"),
+    }
+    1
+}
+
+/// Command line options.
+struct Args {
+    data_dir: Option<PathBuf>, // Overrides the default data directory
+    cookies: Option<String>,   // Overrides FEATHER_COOKIES
+    cookies_from_browser: Option<String>, // Overrides FEATHER_COOKIES_FROM_BROWSER
+    command: Option<Command>,  // Runs headless instead of starting the TUI
+    incognito: bool,           // Keeps headless plays out of the history
+}
+
+impl Args {
+    /// Parses the command line, returning `None` when help was requested.
+    fn parse(args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.peekable();
+        let mut parsed = Args {
+            data_dir: None,
+            cookies: None,
+            cookies_from_browser: None,
+            command: None,
+            incognito: false,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--data-dir" => {
+                    let value = args.next().ok_or("--data-dir needs a path")?;
+                    parsed.data_dir = Some(PathBuf::from(value));
+                }
+                "--cookies" => {
+                    let value = args.next().ok_or("--cookies needs a file")?;
+                    parsed.cookies = Some(value);
+                }
+                "--cookies-from-browser" => {
+                    let value = args
+                        .next()
+                        .ok_or("--cookies-from-browser needs a browser")?;
+                    parsed.cookies_from_browser = Some(value);
+                }
+                "--incognito" => parsed.incognito = true,
+                "-h" | "--help" => return Ok(None),
+                "play" if parsed.command.is_none() => {
+                    // The words up to the next option are the query, so it needs no quoting
+                    let mut rest = Vec::new();
+                    while let Some(word) = args.next_if(|word| !word.starts_with("--")) {
+                        rest.push(word);
+                    }
+                    if rest.is_empty() {
+                        return Err("play needs a query".to_string());
+                    }
+                    parsed.command = Some(Command::Play(rest.join(" ")));
+                }
+                "history" if parsed.command.is_none() => parsed.command = Some(Command::History),
+                "stats" if parsed.command.is_none() => {
+                    parsed.command = Some(Command::Stats);
+                    // The database is all there is to report on, so --db may be given or not
+                    args.next_if(|next| next == "--db");
+                }
+                "compact" if parsed.command.is_none() => parsed.command = Some(Command::Compact),
+                "export" | "import" if parsed.command.is_none() => {
+                    let file = PathBuf::from(args.next().ok_or(format!("{} needs a file", arg))?);
+                    parsed.command = Some(if arg == "export" {
+                        Command::Export(file)
+                    } else {
+                        Command::Import(file)
+                    });
+                }
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+        Ok(Some(parsed))
+    }
+
+    /// Picks the cookies source: options before environment variables,
+    /// and a cookies file before a browser.
+    fn cookie_source(&self) -> Option<CookieSource> {
+        if let Some(file) = &self.cookies {
+            return Some(CookieSource::File(PathBuf::from(file)));
+        }
+        if let Some(browser) = &self.cookies_from_browser {
+            return Some(CookieSource::Browser(browser.clone()));
+        }
+        let env = |name: &str| env::var(name).ok().filter(|value| !value.trim().is_empty());
+        env("FEATHER_COOKIES")
+            .map(|file| CookieSource::File(PathBuf::from(file)))
+            .or_else(|| env("FEATHER_COOKIES_FROM_BROWSER").map(CookieSource::Browser))
+    }
+}
+
 /// Enum representing different states of the application.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum State {
     HelpMode,
     Global,
     Search,
     History,
+    Downloads,
+    Local,
     // UserPlaylist,
     // CurrentPlayingPlaylist,
     SongPlayer,
 }
 
+impl State {
+    /// Parses a start screen name as used by `FEATHER_START_SCREEN`.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "global" => Some(State::Global),
+            "search" => Some(State::Search),
+            "history" => Some(State::History),
+            "downloads" => Some(State::Downloads),
+            "local" => Some(State::Local),
+            "player" => Some(State::SongPlayer),
+            _ => None,
+        }
+    }
+}
+
 /// Main application struct managing the state and UI components.
 struct App<'a> {
     state: State,
+    prev_state: State, // State to return to when leaving the player
     search: Search<'a>,
     history: History,
+    downloads: DownloadsView,
+    local: LocalView,
+    right_pane: State, // History, Downloads or Local, whichever was shown last
+    lyrics: LyricsView,
+    show_lyrics: bool, // Lyrics replace the right pane until one of its views is opened again
     // user_playlist: UserPlaylist,
     // current_playling_playlist: CurrentPlayingPlaylist,
     top_bar: TopBar,
+    status_bar: StatusBar,
     player: SongPlayer,
-    // backend: Arc<Backend>,
+    backend: Arc<Backend>, // For app-wide actions such as reloading cookies
+    help: Help,
     help_mode: bool,
+    help_return: State, // State to return to when help is closed
     exit: bool,
+    confirm_quit: bool, // Ask before quitting while a song plays
+    media_keys: bool,   // Whether Ctrl+Space, Ctrl+N and Ctrl+P control playback everywhere
+    quit_prompt: bool,  // Whether the quit confirmation is showing
+    search_area: Rect,  // Last rendered area of each pane, for mouse routing
+    history_area: Rect,
+    player_area: Rect,
+    last_click: Option<(Instant, u16, u16)>, // Time and cell of the previous left click
+    split_percent: u16,                      // Width of the Search pane relative to History
+    redraw: Redraw,                          // Frame requests from background tasks
+    _scrobbler: Option<Scrobbler>,           // Stopped when the app is dropped
+    _sponsorblock: Option<SponsorBlock>,     // Stopped when the app is dropped
+    #[cfg(unix)]
+    _ipc: Option<IpcServer>, // Socket removed when the app is dropped
+    #[cfg(unix)]
+    _suspend: Option<PauseOnSuspend>, // Stops listening for SIGUSR2 when dropped
+    _now_playing: Option<NowPlayingFile>,    // Reset to idle when the app is dropped
+    _backup: Option<AutoBackup>,             // Stopped when the app is dropped
+    #[cfg(feature = "mpris")]
+    _mpris: Option<Mpris>, // Stopped when the app is dropped
+    #[cfg(feature = "discord")]
+    _discord: Option<DiscordPresence>, // Cleared when the app is dropped
 }
 
 impl App<'_> {
     /// Creates a new instance of the application.
-    fn new() -> Self {
-        let history = Arc::new(HistoryDB::new().unwrap());
-        let get_cookies = env::var("FEATHER_COOKIES").ok(); // Fetch cookies from environment variables if available.
-        let backend = Arc::new(Backend::new(history.clone(), get_cookies).unwrap());
-        let (tx, rx) = mpsc::channel(32);
+    fn new(args: Args, history: Arc<HistoryDB>, backend: Arc<Backend>) -> Self {
+        let paths = args.data_dir.map(Paths::new).unwrap_or_default();
+        let status_bar = StatusBar::new();
+        let redraw = Redraw::new();
+        {
+            let status_bar = status_bar.clone();
+            let redraw = redraw.clone();
+            backend.yt.on_switch(move |provider| {
+                status_bar.set_error(
+                    format!("YouTube requests keep failing, switched to {}", provider),
+                    ERROR_DURATION,
+                );
+                redraw.request();
+            });
+        }
+        if backend.has_cookies() {
+            reload_cookies(&backend, &status_bar, &redraw);
+        }
+        let proxy = Proxy::from_env().ok().flatten();
+        if proxy.as_ref().is_some_and(|proxy| !proxy.is_http()) {
+            status_bar.set_error(
+                "mpv only supports http:// proxies, songs stream without the proxy",
+                ERROR_DURATION,
+            );
+        }
+
+        let sponsorblock = SponsorBlock::start(backend.clone(), status_bar.clone(), redraw.clone());
+        #[cfg(unix)]
+        let suspend = PauseOnSuspend::start(backend.clone(), status_bar.clone(), redraw.clone());
+        let backup = AutoBackup::start(history.clone(), &paths.data_dir);
+
+        let start_state = env::var("FEATHER_START_SCREEN")
+            .ok()
+            .and_then(|name| State::from_name(&name))
+            .unwrap_or(State::Global);
 
         App {
-            state: State::Global,
-            search: Search::new(backend.clone(), tx.clone()),
-            history: History::new(history, backend.clone(), tx.clone()),
+            state: start_state,
+            prev_state: State::Global,
+            search: Search::new(backend.clone(), status_bar.clone(), redraw.clone()),
+            history: History::new(history, backend.clone(), status_bar.clone(), redraw.clone()),
+            downloads: DownloadsView::new(backend.clone(), status_bar.clone(), redraw.clone()),
+            local: LocalView::new(backend.clone(), status_bar.clone(), redraw.clone()),
+            right_pane: State::History,
+            lyrics: LyricsView::new(backend.clone(), redraw.clone()),
+            show_lyrics: false,
             // user_playlist: UserPlaylist {},
             // current_playling_playlist: CurrentPlayingPlaylist {},
-            top_bar: TopBar::new(),
-            player: SongPlayer::new(backend.clone(), rx),
-            // backend,
+            top_bar: TopBar::new(proxy.is_some()),
+            player: SongPlayer::new(backend.clone(), status_bar.clone(), redraw.clone()),
+            status_bar,
+            backend: backend.clone(),
+            help: Help::new(),
             help_mode: false,
+            help_return: State::Global,
             exit: false,
+            confirm_quit: env::var("FEATHER_CONFIRM_QUIT").as_deref() != Ok("0"),
+            media_keys: env::var("FEATHER_MEDIA_KEYS").as_deref() != Ok("0"),
+            quit_prompt: false,
+            search_area: Rect::default(),
+            history_area: Rect::default(),
+            player_area: Rect::default(),
+            last_click: None,
+            split_percent: env::var("FEATHER_SPLIT_PERCENT")
+                .ok()
+                .and_then(|v| v.trim().parse::<u16>().ok())
+                .unwrap_or(50)
+                .clamp(MIN_SPLIT, MAX_SPLIT),
+            redraw,
+            _scrobbler: Scrobbler::start(backend.clone()),
+            _sponsorblock: sponsorblock,
+            #[cfg(unix)]
+            _ipc: IpcServer::start(backend.clone()),
+            #[cfg(unix)]
+            _suspend: suspend,
+            _now_playing: NowPlayingFile::start(backend.clone()),
+            _backup: backup,
+            #[cfg(feature = "discord")]
+            _discord: DiscordPresence::start(backend.clone()),
+            #[cfg(feature = "mpris")]
+            _mpris: Mpris::start(backend),
+        }
+    }
+
+    /// Opens help scrolled to the bindings of the current state.
+    fn open_help(&mut self) {
+        let section = match self.state {
+            State::Search if self.search.is_typing() => "Search Bar",
+            State::Search => "Search Results",
+            State::History => "History",
+            State::Downloads => "Downloads",
+            State::Local => "Local Music",
+            State::SongPlayer => "Player",
+            State::Global | State::HelpMode => "Global",
+        };
+        self.help.show_section(section);
+        self.help_return = self.state;
+        self.help_mode = true;
+        self.state = State::HelpMode;
+    }
+
+    /// Quits right away when idle, otherwise asks first unless disabled.
+    fn request_quit(&mut self) {
+        if self.confirm_quit && self.player.is_playing() {
+            self.quit_prompt = true;
+        } else {
+            self.exit = true;
+        }
+    }
+
+    /// Returns the key hints for the status bar in the current state.
+    fn hints(&self) -> &'static str {
+        if self.quit_prompt {
+            return "y Quit · n Cancel";
+        }
+        match self.state {
+            State::Global => {
+                "s Search · h History · d Downloads · l Local · p Player · < > Resize · ? Help · Esc Quit"
+            }
+            State::Search if self.search.is_typing() => {
+                "Enter Search · Alt+Enter Play first · Tab Results · Esc Back"
+            }
+            State::Search => {
+                "Enter Play · Tab Search bar · A Artist · D Download · y Copy URL · o Open · ? Help · Esc Back"
+            }
+            State::History => {
+                "Enter Play · d Delete · A Artist · D Download · y Copy URL · o Open · ? Help · Esc Back"
+            }
+            State::Downloads => "Enter Play · d Delete file · ? Help · Esc Back",
+            State::Local => "Enter Play · r Rescan folders · ? Help · Esc Back",
+            State::SongPlayer => {
+                "Space Pause · ← → Seek · y Copy URL · o Open · L Lyrics · ? Help · p Back"
+            }
+            State::HelpMode => "j k Scroll · g G Top/Bottom · Esc Close",
         }
     }
 
     /// Handles global keystrokes and state transitions.
     fn handle_global_keystrokes(&mut self, key: KeyEvent) {
+        if !is_press(&key) {
+            return;
+        }
+        // Ctrl+C quits from every state, even while typing
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if self.quit_prompt {
+                self.exit = true; // A second Ctrl+C confirms
+            } else {
+                self.request_quit();
+            }
+            return;
+        }
+        if self.quit_prompt {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => self.exit = true,
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => self.quit_prompt = false,
+                _ => (),
+            }
+            return;
+        }
+        let typing = self.state == State::Search && self.search.is_typing();
+        if self.media_keys && !typing && self.handle_media_keys(key) {
+            return;
+        }
+        // `?` opens help from anywhere except while typing a query
+        if key.code == KeyCode::Char('?') && self.state != State::HelpMode && !typing {
+            self.open_help();
+            return;
+        }
         match self.state {
             State::Global => match key.code {
-                KeyCode::Char('s') => self.state = State::Search,
-                KeyCode::Char('h') => self.state = State::History,
-                KeyCode::Char('p') => self.state = State::SongPlayer,
-                KeyCode::Char('?') => {
-                    self.help_mode = true;
-                    self.state = State::HelpMode;
-                }
-                KeyCode::Esc => {
-                    self.exit = true;
-                }
-                _ => (),
+                KeyCode::Esc => self.request_quit(),
+                _ => self.handle_view_switch(key),
             },
             State::Search => match key.code {
                 KeyCode::Esc => self.state = State::Global,
-                _ => self.search.handle_keystrokes(key),
+                _ => {
+                    if !self.search.handle_keystrokes(key) {
+                        self.handle_view_switch(key);
+                    }
+                }
             },
             State::HelpMode => match key.code {
-                KeyCode::Esc => {
-                    self.state = State::Global;
+                KeyCode::Esc | KeyCode::Char('?') => {
+                    self.state = self.help_return;
                     self.help_mode = false;
                 }
-                _ => (),
+                _ => {
+                    self.help.handle_keystrokes(key);
+                }
             },
             State::History => match key.code {
                 KeyCode::Esc => self.state = State::Global,
-                _ => self.history.handle_keystrokes(key),
+                KeyCode::Char('A') => {
+                    // Pivot to the selected song's artist in the search results
+                    if let Some(artist) = self.history.selected_artist() {
+                        self.search.browse_artist(artist);
+                        self.state = State::Search;
+                    }
+                }
+                _ => {
+                    if !self.history.handle_keystrokes(key) {
+                        self.handle_view_switch(key);
+                    }
+                }
+            },
+            State::Downloads => match key.code {
+                KeyCode::Esc => self.state = State::Global,
+                _ => {
+                    if !self.downloads.handle_keystrokes(key) {
+                        self.handle_view_switch(key);
+                    }
+                }
             },
-            State::SongPlayer => match key.code {
+            State::Local => match key.code {
                 KeyCode::Esc => self.state = State::Global,
-                _ => self.player.handle_keystrokes(key),
+                _ => {
+                    if !self.local.handle_keystrokes(key) {
+                        self.handle_view_switch(key);
+                    }
+                }
+            },
+            State::SongPlayer => match key.code {
+                // Leaving the player returns to the view it was opened from
+                KeyCode::Esc | KeyCode::Char('p') => self.state = self.prev_state,
+                KeyCode::Char('L') => self.show_lyrics = !self.show_lyrics,
+                _ => {
+                    if !self.player.handle_keystrokes(key) {
+                        self.handle_view_switch(key);
+                    }
+                }
             },
         }
     }
 
+    /// Handles the playback bindings that work from every view, confirming each in the
+    /// status bar since the player may not be in view. Returns whether the key was used.
+    fn handle_media_keys(&self, key: KeyEvent) -> bool {
+        if !key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        if !matches!(key.code, KeyCode::Char(' ' | 'n' | 'p')) {
+            return false;
+        }
+        if self.backend.current_song_id().is_none() {
+            self.status_bar
+                .set_message("Nothing is playing", MESSAGE_DURATION);
+            return true;
+        }
+        let player = &self.backend.player;
+        match key.code {
+            KeyCode::Char(' ') => match player.play_pause().and_then(|_| player.is_playing()) {
+                Ok(playing) => self
+                    .status_bar
+                    .set_message(if playing { "Playing" } else { "Paused" }, MESSAGE_DURATION),
+                Err(e) => self.status_bar.set_error(e.to_string(), ERROR_DURATION),
+            },
+            KeyCode::Char('p') => self.status_bar.report(
+                player.seek_to(0.0).map_err(|e| e.to_string()),
+                "Restarted the song",
+            ),
+            _ => {
+                self.status_bar
+                    .set_message("Finding a related song…", MESSAGE_DURATION);
+                let backend = Arc::clone(&self.backend);
+                let status = self.status_bar.clone();
+                let redraw = self.redraw.clone();
+                tokio::spawn(async move {
+                    match backend.play_related().await {
+                        Ok(song) => status
+                            .set_message(format!("Playing {}", song.song_name), MESSAGE_DURATION),
+                        Err(BackendError::Superseded) => (),
+                        Err(e) => status.set_error(e.to_string(), ERROR_DURATION),
+                    }
+                    redraw.request();
+                });
+            }
+        }
+        true
+    }
+
+    /// Focuses a view of the right pane, bringing it back in place of the lyrics.
+    fn open_right_pane(&mut self, view: State) {
+        self.show_lyrics = false;
+        self.state = view;
+    }
+
+    /// Handles the bindings of Global mode that also apply when a focused pane
+    /// leaves a key unused, such as switching views and resizing the split.
+    fn handle_view_switch(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('s') => self.state = State::Search,
+            KeyCode::Char('h') => self.open_right_pane(State::History),
+            KeyCode::Char('d') => self.open_right_pane(State::Downloads),
+            KeyCode::Char('l') => self.open_right_pane(State::Local),
+            KeyCode::Char('p') if self.state != State::SongPlayer => {
+                self.prev_state = self.state;
+                self.state = State::SongPlayer;
+            }
+            KeyCode::Char('C') => reload_cookies(&self.backend, &self.status_bar, &self.redraw),
+            KeyCode::Char('<') => {
+                self.split_percent = self.split_percent.saturating_sub(SPLIT_STEP).max(MIN_SPLIT);
+            }
+            KeyCode::Char('>') => {
+                self.split_percent = (self.split_percent + SPLIT_STEP).min(MAX_SPLIT);
+            }
+            _ => (),
+        }
+    }
+
+    /// Routes mouse clicks and wheel scrolls to the pane under the cursor.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.help_mode || self.quit_prompt {
+            return;
+        }
+        if self.player.handle_mouse(mouse) {
+            return; // Seeking on the progress bar
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let now = Instant::now();
+                let double = self.last_click.is_some_and(|(time, column, row)| {
+                    now.duration_since(time) < DOUBLE_CLICK
+                        && column == mouse.column
+                        && row == mouse.row
+                });
+                self.last_click = if double {
+                    None // A third click starts a new pair
+                } else {
+                    Some((now, mouse.column, mouse.row))
+                };
+                // A double click on a row acts like Enter
+                let enter = KeyEvent::from(KeyCode::Enter);
+                if let Some(tab) = self.top_bar.tab_at(mouse.column, mouse.row) {
+                    if tab == State::SongPlayer && self.state != State::SongPlayer {
+                        self.prev_state = self.state;
+                    }
+                    if matches!(tab, State::History | State::Downloads | State::Local) {
+                        self.show_lyrics = false;
+                    }
+                    self.state = tab;
+                } else if self.search_area.contains(position) {
+                    self.state = State::Search;
+                    if self.search.click(mouse.column, mouse.row) && double {
+                        self.search.handle_keystrokes(enter);
+                    }
+                } else if self.history_area.contains(position) && !self.show_lyrics {
+                    self.state = self.right_pane;
+                    let hit = match self.right_pane {
+                        State::Downloads => self.downloads.click(mouse.column, mouse.row),
+                        State::Local => self.local.click(mouse.column, mouse.row),
+                        _ => self.history.click(mouse.column, mouse.row),
+                    };
+                    if hit && double {
+                        match self.right_pane {
+                            State::Downloads => self.downloads.handle_keystrokes(enter),
+                            State::Local => self.local.handle_keystrokes(enter),
+                            _ => self.history.handle_keystrokes(enter),
+                        };
+                    }
+                } else if self.player_area.contains(position) && self.state != State::SongPlayer {
+                    self.prev_state = self.state;
+                    self.state = State::SongPlayer;
+                }
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let down = mouse.kind == MouseEventKind::ScrollDown;
+                if self.search_area.contains(position) {
+                    self.search.scroll(down);
+                } else if self.history_area.contains(position) && !self.show_lyrics {
+                    match self.right_pane {
+                        State::Downloads => self.downloads.scroll(down),
+                        State::Local => self.local.scroll(down),
+                        _ => self.history.scroll(down),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Draws one frame of the whole UI.
+    fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        // Short terminals get a one-line top bar and no status bar,
+        // narrow ones a one-line player
+        let short = area.height < COMPACT_HEIGHT;
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                if short {
+                    Constraint::Length(1)
+                } else {
+                    Constraint::Percentage(10)
+                },
+                Constraint::Min(0),
+                if area.width < COMPACT_WIDTH {
+                    Constraint::Length(1)
+                } else {
+                    Constraint::Percentage(15)
+                },
+                Constraint::Length(if short { 0 } else { 1 }),
+            ])
+            .split(area);
+
+        let middle_layout = Layout::default()
+            .direction(ratatui::layout::Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Percentage(self.split_percent),
+                Constraint::Percentage(100 - self.split_percent),
+            ])
+            .split(layout[1]);
+
+        if matches!(self.state, State::History | State::Downloads | State::Local) {
+            self.right_pane = self.state;
+        }
+        self.search_area = middle_layout[0];
+        self.history_area = middle_layout[1];
+        self.player_area = layout[2];
+
+        if !self.help_mode {
+            self.top_bar
+                .render(layout[0], frame.buffer_mut(), self.state, self.prev_state);
+            let focus = self.state;
+            self.search
+                .render(middle_layout[0], frame.buffer_mut(), focus == State::Search);
+            let right = middle_layout[1];
+            let focused = focus == self.right_pane;
+            match self.right_pane {
+                _ if self.show_lyrics => self.lyrics.render(right, frame.buffer_mut(), false),
+                State::Downloads => self.downloads.render(right, frame.buffer_mut(), focused),
+                State::Local => self.local.render(right, frame.buffer_mut(), focused),
+                _ => self.history.render(right, frame.buffer_mut(), focused),
+            }
+            self.player
+                .render(layout[2], frame.buffer_mut(), focus == State::SongPlayer);
+        } else {
+            // Help covers everything but the status bar
+            let help_area = Rect {
+                height: area.height.saturating_sub(layout[3].height),
+                ..area
+            };
+            self.help.render(help_area, frame.buffer_mut());
+        }
+        let pending = match self.state {
+            State::Search => self.search.pending_count(),
+            State::History => self.history.pending_count(),
+            State::Downloads => self.downloads.pending_count(),
+            State::Local => self.local.pending_count(),
+            State::HelpMode => self.help.pending_count(),
+            _ => None,
+        };
+        self.status_bar.render(
+            layout[3],
+            frame.buffer_mut(),
+            self.hints(),
+            &pending.map(|count| count.to_string()).unwrap_or_default(),
+        );
+
+        if self.quit_prompt {
+            render_quit_prompt(area, frame.buffer_mut());
+        }
+    }
+
     /// Main render loop for updating the UI.
     async fn render(mut self, mut terminal: DefaultTerminal) {
-        let mut redraw_interval = interval(Duration::from_millis(250)); // Redraw every 250ms
+        let mut tick = interval(TICK);
+        let mut rx_events = spawn_event_reader();
+        let redraw = self.redraw.clone();
+
+        let mut frame_timer = FrameTimer::new();
 
         while !self.exit {
-            terminal
-                .draw(|frame| {
-                    let area = frame.area();
-                    let layout = Layout::default()
-                        .direction(ratatui::layout::Direction::Vertical)
-                        .constraints([
-                            Constraint::Percentage(10),
-                            Constraint::Percentage(75),
-                            Constraint::Percentage(15),
-                        ])
-                        .split(area);
-
-                    let middle_layout = Layout::default()
-                        .direction(ratatui::layout::Direction::Horizontal)
-                        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(layout[1]);
-
-                    if !self.help_mode {
-                        self.top_bar
-                            .render(layout[0], frame.buffer_mut(), &self.state);
-                        self.search.render(middle_layout[0], frame.buffer_mut());
-                        self.history.render(middle_layout[1], frame.buffer_mut());
-                        self.player.render(layout[2], frame.buffer_mut());
-                    } else {
-                        let rows = vec![
-                            Row::new(vec![Cell::from("s"), Cell::from("Search")]),
-                            Row::new(vec![Cell::from("h"), Cell::from("History")]),
-                            Row::new(vec![Cell::from("p"), Cell::from("Player")]),
-                            Row::new(vec![Cell::from("?"), Cell::from("Toggle Help Mode")]),
-                            Row::new(vec![
-                                Cell::from("TAB (Search)"),
-                                Cell::from("Toggle between search input and results"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("Esc (Global)"),
-                                Cell::from("Quit application"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("Esc (Non-Global)"),
-                                Cell::from("Switch to Global Mode"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("↑ / k(History/Search)"),
-                                Cell::from("Navigate up in list"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("↓ / j(History/Search)"),
-                                Cell::from("Navigate down in list"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("Space / ; (Player)"),
-                                Cell::from("Pause current song"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("→ (Player)"),
-                                Cell::from("Skip forward 5 seconds"),
-                            ]),
-                            Row::new(vec![
-                                Cell::from("← (Player)"),
-                                Cell::from("Rewind 5 seconds"),
-                            ]),
-                        ];
-
-                        let help_table = Table::new(
-                            rows,
-                            [Constraint::Percentage(20), Constraint::Percentage(80)],
-                        )
-                        .block(Block::default().borders(Borders::ALL).title("Help"))
-                        .header(Row::new(vec![Cell::from("Key"), Cell::from("Action")]));
-
-                        help_table.render(area, frame.buffer_mut());
-                    }
-                })
-                .unwrap();
+            let started = Instant::now();
+            // A frame that fails to draw is retried on the next tick instead of aborting
+            let _ = terminal.draw(|frame| self.draw(frame));
+            frame_timer.record(started.elapsed());
 
+            // Sleep until there is something new to draw
             tokio::select! {
-                _ = redraw_interval.tick() => {}
-                _ = async {
-                    if poll(Duration::from_millis(100)).unwrap() {
-                        if let Event::Key(key) = read().unwrap() {
-                            self.handle_global_keystrokes(key);
-                        }
+                _ = tick.tick() => {}
+                _ = redraw.requested() => {}
+                event = rx_events.recv() => match event {
+                    Some(Event::Key(key)) => self.handle_global_keystrokes(key),
+                    Some(Event::Mouse(mouse)) => self.handle_mouse(mouse),
+                    // Resize the buffers now so the next frame is a clean full repaint
+                    Some(Event::Resize(_, _)) => {
+                        let _ = terminal.autoresize();
                     }
-                } => {}
+                    Some(_) => (),
+                    None => self.exit = true, // The terminal is gone
+                },
             }
         }
+        // Stop mpv and the background tasks and save the history before the views drop
+        if let Err(e) = self.backend.shutdown() {
+            debug_log::log(&format!("shutdown: {}", e));
+        }
     }
 }
 
+/// Logs tasks still running shortly after shutdown, which should have been stopped with
+/// the backend or aborted by their owner. Debug builds only.
+#[cfg(debug_assertions)]
+async fn report_leftover_tasks() {
+    let runtime = tokio::runtime::Handle::current();
+    let deadline = Instant::now() + SHUTDOWN_GRACE;
+    while runtime.metrics().num_alive_tasks() > 0 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let alive = runtime.metrics().num_alive_tasks();
+    if alive > 0 {
+        debug_log::log(&format!("shutdown: {} tasks still running", alive));
+    }
+}
+
+/// Reads terminal events on a dedicated thread, since crossterm's `read` blocks.
+fn spawn_event_reader() -> mpsc::Receiver<Event> {
+    let (tx, rx) = mpsc::channel(64);
+    std::thread::spawn(move || {
+        loop {
+            match read() {
+                Ok(event) => {
+                    if tx.blocking_send(event).is_err() {
+                        break; // The render loop has exited
+                    }
+                }
+                // Transient read errors are retried rather than ending input for good
+                Err(_) => std::thread::sleep(Duration::from_millis(100)),
+            }
+        }
+    });
+    rx
+}
+
+/// Returns whether a key event is a press. Windows and kitty-protocol terminals also
+/// report releases and repeats, which would otherwise act twice.
+fn is_press(key: &KeyEvent) -> bool {
+    key.kind == KeyEventKind::Press
+}
+
+/// Reloads the cookies in the background, reporting the outcome in the status bar.
+fn reload_cookies(backend: &Arc<Backend>, status: &StatusBar, redraw: &Redraw) {
+    let backend = Arc::clone(backend);
+    let status = status.clone();
+    let redraw = redraw.clone();
+    tokio::spawn(async move {
+        let loaded = backend
+            .reload_cookies()
+            .await
+            .map(|count| format!("Loaded {} YouTube cookies", count));
+        match loaded {
+            Ok(message) => status.set_message(message, MESSAGE_DURATION),
+            Err(e) => status.set_error(e.to_string(), ERROR_DURATION),
+        }
+        redraw.request();
+    });
+}
+
+/// Draws the yes/no popup shown when quitting during playback.
+fn render_quit_prompt(area: Rect, buf: &mut Buffer) {
+    let width = 40.min(area.width);
+    let height = 4.min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    Clear.render(popup, buf);
+    Paragraph::new(vec![
+        "A song is playing.".into(),
+        "Quit? (y)es / (n)o".into(),
+    ])
+    .alignment(Alignment::Center)
+    .block(Block::default().title("Quit Feather").borders(Borders::ALL))
+    .render(popup, buf);
+}
+
 /// Represents the top bar UI component.
-struct TopBar;
+struct TopBar {
+    tabs: Vec<(Rect, State)>, // Where each tab was last drawn, for mouse clicks
+    proxy: bool,              // Whether traffic goes through a proxy, shown in the title
+}
 
 impl TopBar {
-    fn new() -> Self {
-        Self
+    /// Tabs in display order with the key that opens them.
+    const TABS: [(&'static str, char, State); 5] = [
+        ("Search", 's', State::Search),
+        ("History", 'h', State::History),
+        ("Downloads", 'd', State::Downloads),
+        ("Local", 'l', State::Local),
+        ("Player", 'p', State::SongPlayer),
+    ];
+
+    fn new(proxy: bool) -> Self {
+        Self {
+            tabs: Vec::new(),
+            proxy,
+        }
+    }
+
+    /// Draws the tabs, highlighting the active one. While the player is open the view
+    /// behind it stays on screen, so its tab is marked as well.
+    fn render(&mut self, area: Rect, buf: &mut Buffer, state: State, prev_state: State) {
+        let proxy = if self.proxy { " · proxy: on" } else { "" };
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Feather v{}{}",
+            env!("CARGO_PKG_VERSION"),
+            proxy
+        ));
+        // Without room for borders the tabs take the whole line
+        let inner = if area.height < 3 {
+            area
+        } else {
+            let inner = block.inner(area);
+            block.render(area, buf);
+            inner
+        };
+
+        self.tabs.clear();
+        let mut spans = Vec::new();
+        let mut x = inner.x;
+        for (i, (name, key, tab)) in Self::TABS.into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" │ "));
+                x += 3;
+            }
+            let style = if tab == state {
+                accent_style().add_modifier(Modifier::BOLD)
+            } else if state == State::SongPlayer && tab == prev_state {
+                accent_style().add_modifier(Modifier::DIM)
+            } else {
+                Style::default()
+            };
+            let label = format!("{} [{}]", name, key);
+            let width = label.len() as u16;
+            self.tabs
+                .push((Rect::new(x, inner.y, width, 1).intersection(inner), tab));
+            spans.push(Span::styled(label, style));
+            x += width;
+        }
+        Paragraph::new(Line::from(spans)).render(inner, buf);
     }
-    fn render(&mut self, area: Rect, buf: &mut Buffer, state: &State) {
-        let s = format!("Feather | Current Mode : {:?}", state);
-        Paragraph::new(s)
-            .block(Block::default().borders(Borders::ALL))
-            .render(area, buf);
+
+    /// Returns the tab drawn at the given cell, if any.
+    fn tab_at(&self, column: u16, row: u16) -> Option<State> {
+        self.tabs
+            .iter()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .map(|(_, tab)| *tab)
     }
 }
 
@@ -225,3 +1034,155 @@ struct UserPlaylist {}
 #[allow(unused)]
 /// Placeholder struct for currently playing playlist.
 struct CurrentPlayingPlaylist {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventState;
+    use feather::testing::TestBackend;
+    use ratatui::{Terminal, backend::TestBackend as TestTerminal};
+
+    fn app(test: &TestBackend) -> App<'static> {
+        let args = [
+            "--data-dir".to_string(),
+            test.dir.path().display().to_string(),
+        ];
+        let args = Args::parse(args.into_iter()).unwrap().unwrap();
+        App::new(args, test.backend.history.clone(), test.backend.clone())
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_global_keystrokes(KeyEvent::from(code));
+    }
+
+    // Draws a frame of the given size, returning its rows as text
+    fn draw(app: &mut App, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestTerminal::new(width, height)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    fn shows(rows: &[String], text: &str) -> bool {
+        rows.iter().any(|row| row.contains(text))
+    }
+
+    #[test]
+    fn only_key_presses_are_handled() {
+        let key = |kind| KeyEvent {
+            code: KeyCode::Char('q'),
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::NONE,
+        };
+        assert!(is_press(&key(KeyEventKind::Press)));
+        assert!(!is_press(&key(KeyEventKind::Release)));
+        assert!(!is_press(&key(KeyEventKind::Repeat)));
+    }
+
+    #[test]
+    fn start_screens_parse_by_name() {
+        let cases = [
+            ("global", Some(State::Global)),
+            ("search", Some(State::Search)),
+            (" History ", Some(State::History)),
+            ("DOWNLOADS", Some(State::Downloads)),
+            ("local", Some(State::Local)),
+            ("player", Some(State::SongPlayer)),
+            ("help", None),
+            ("", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(State::from_name(name), expected, "{:?}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn leaving_the_player_returns_to_where_it_was_opened() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        assert_eq!(app.state, State::Global);
+
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.state, State::SongPlayer);
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.state, State::Global);
+
+        press(&mut app, KeyCode::Char('h'));
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(
+            (app.state, app.prev_state),
+            (State::SongPlayer, State::History)
+        );
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.state, State::History);
+    }
+
+    #[tokio::test]
+    async fn help_opened_in_the_player_returns_to_it() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('p'));
+
+        press(&mut app, KeyCode::Char('?'));
+        assert_eq!(app.state, State::HelpMode);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.state, State::SongPlayer);
+        // The view behind the player is still remembered
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.state, State::Downloads);
+    }
+
+    #[tokio::test]
+    async fn every_view_draws_at_any_size() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        let sizes = [(1, 1), (20, 5), (40, 10), (59, 24), (80, 25), (200, 60)];
+        for keys in ["", "s", "h", "d", "l", "p", "?"] {
+            app.state = State::Global;
+            app.help_mode = false;
+            for key in keys.chars() {
+                press(&mut app, KeyCode::Char(key));
+            }
+            for (width, height) in sizes {
+                draw(&mut app, width, height); // Panics on out-of-bounds areas
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn full_size_frame_shows_every_pane() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        let rows = draw(&mut app, 100, 30);
+        assert!(rows[0].starts_with('┌'));
+        assert!(shows(&rows, "Feather v"));
+        assert!(shows(&rows, "Search [s] │ History [h]"));
+        assert!(shows(&rows, "Search Song or Playlist"));
+        assert!(shows(&rows, "No song is playing"));
+        assert!(rows[29].starts_with("s Search · h History"));
+    }
+
+    #[tokio::test]
+    async fn short_terminals_get_a_one_line_top_bar_and_no_status_bar() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        let rows = draw(&mut app, 100, COMPACT_HEIGHT - 1);
+        assert!(rows[0].starts_with("Search [s] │ History [h]"));
+        assert!(!shows(&rows, "Feather v"));
+        assert!(!shows(&rows, "s Search · h History"));
+    }
+
+    #[tokio::test]
+    async fn narrow_terminals_get_a_one_line_player() {
+        let test = TestBackend::new();
+        let mut app = app(&test);
+        let rows = draw(&mut app, COMPACT_WIDTH - 1, 30);
+        assert!(rows[28].starts_with("No song is playing"));
+        assert!(rows[29].starts_with("s Search"));
+        assert!(rows[27].ends_with('┘')); // The panes end right above it
+    }
+}
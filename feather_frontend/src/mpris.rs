@@ -0,0 +1,198 @@
+use feather::backend::{Backend, Song};
+use mpris_server::{Metadata, PlaybackStatus, Player, Time, TrackId};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::LocalSet;
+
+const BUS_NAME: &str = "feather"; // Registered as org.mpris.MediaPlayer2.feather
+const POLL_INTERVAL: Duration = Duration::from_secs(1); // How often mpv state is mirrored
+
+// MPRIS2 server exposing playback to media keys, playerctl and desktop widgets.
+// The D-Bus player is not `Send`, so it lives on its own thread with a local task set;
+// dropping this handle stops the server and waits for the thread to finish.
+pub struct Mpris {
+    shutdown: Option<oneshot::Sender<()>>, // Ends the server loop
+    thread: Option<JoinHandle<()>>,        // Thread running the server
+}
+
+impl Mpris {
+    // Starts the server unless FEATHER_MPRIS is set to 0
+    pub fn start(backend: Arc<Backend>) -> Option<Self> {
+        if std::env::var("FEATHER_MPRIS").as_deref() == Ok("0") {
+            return None;
+        }
+        let (shutdown, rx_shutdown) = oneshot::channel();
+        let thread = thread::Builder::new()
+            .name("mpris".to_string())
+            .spawn(move || {
+                let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                else {
+                    return;
+                };
+                // Without a session bus there is nobody to talk to, so failures end quietly
+                let _ = LocalSet::new().block_on(&runtime, serve(backend, rx_shutdown));
+            })
+            .ok()?;
+        Some(Self {
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Mpris {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Registers the player on the session bus and mirrors song changes and mpv state into it
+async fn serve(
+    backend: Arc<Backend>,
+    mut rx_shutdown: oneshot::Receiver<()>,
+) -> mpris_server::zbus::Result<()> {
+    let player = Player::builder(BUS_NAME)
+        .identity("Feather")
+        .can_play(true)
+        .can_pause(true)
+        .can_seek(true)
+        .can_control(true)
+        .can_go_previous(true) // Restarts the song, there is no queue to go back in
+        .can_go_next(false)
+        .build()
+        .await?;
+    connect_controls(&player, &backend);
+
+    let mut songs = backend.subscribe();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    let mut status = PlaybackStatus::Stopped;
+    let mut length_known = false; // mpv reports the duration once the stream has loaded
+    let mut art = None; // Thumbnail of the current song
+    let run = player.run();
+    tokio::pin!(run);
+    loop {
+        tokio::select! {
+            _ = &mut run => break,
+            _ = &mut rx_shutdown => break,
+            changed = songs.changed() => {
+                if changed.is_err() {
+                    break; // Backend is gone
+                }
+                let song = songs.borrow_and_update().clone();
+                if let Some(song) = song {
+                    // The song's details give the cover, and the length before mpv knows it;
+                    // those it came without are looked up
+                    let details = if song.duration_secs.is_some() && song.thumbnail.is_some() {
+                        None
+                    } else {
+                        backend.metadata(&song.song_id).await.ok().flatten()
+                    };
+                    art = song
+                        .thumbnail
+                        .clone()
+                        .or_else(|| details.as_ref().and_then(|details| details.thumbnail.clone()));
+                    let length = song
+                        .duration_secs
+                        .map(|secs| secs as f64)
+                        .or_else(|| details.and_then(|details| details.duration).map(f64::from));
+                    player.set_metadata(metadata(&song, length, art.as_deref())).await?;
+                    length_known = false;
+                }
+            }
+            _ = poll.tick() => {
+                let current = match backend.player.is_playing() {
+                    Ok(true) => PlaybackStatus::Playing,
+                    Ok(false) if backend.current_song_id().is_some() => PlaybackStatus::Paused,
+                    _ => PlaybackStatus::Stopped,
+                };
+                if current != status {
+                    player.set_playback_status(current).await?;
+                    status = current;
+                }
+                let position = backend.player.position_secs();
+                player.set_position(Time::from_micros((position * 1e6) as i64));
+                let length = backend.player.duration_secs();
+                if !length_known && length > 0.0 {
+                    let song = songs.borrow().clone();
+                    if let Some(song) = song {
+                        player
+                            .set_metadata(metadata(&song, Some(length), art.as_deref()))
+                            .await?;
+                        length_known = true;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Routes the D-Bus method calls to mpv
+fn connect_controls(player: &Player, backend: &Arc<Backend>) {
+    let b = Arc::clone(backend);
+    player.connect_play_pause(move |_| {
+        let _ = b.player.play_pause();
+    });
+    let b = Arc::clone(backend);
+    player.connect_play(move |_| {
+        let _ = b.player.unpause();
+    });
+    let b = Arc::clone(backend);
+    player.connect_pause(move |_| {
+        let _ = b.player.pause();
+    });
+    let b = Arc::clone(backend);
+    player.connect_stop(move |_| {
+        let _ = b.player.pause();
+    });
+    let b = Arc::clone(backend);
+    player.connect_previous(move |_| {
+        let _ = b.player.seek_to(0.0);
+    });
+    let b = Arc::clone(backend);
+    player.connect_seek(move |_, offset| {
+        let _ = b.player.seek_by(offset.as_micros() as f64 / 1e6);
+    });
+    let b = Arc::clone(backend);
+    player.connect_set_position(move |_, _, position| {
+        let _ = b.player.seek_to(position.as_micros() as f64 / 1e6);
+    });
+}
+
+// Describes a song the way MPRIS clients expect, with the length and cover once they are known
+fn metadata(song: &Song, length: Option<f64>, art: Option<&str>) -> Metadata {
+    let mut builder = Metadata::builder()
+        .title(song.song_name.clone())
+        .artist(song.artist_name.clone())
+        .url(song.url());
+    if let Ok(track_id) = TrackId::try_from(track_path(&song.song_id)) {
+        builder = builder.trackid(track_id);
+    }
+    if let Some(length) = length {
+        builder = builder.length(Time::from_micros((length * 1e6) as i64));
+    }
+    if let Some(art) = art {
+        builder = builder.art_url(art);
+    }
+    builder.build()
+}
+
+// Track ids are D-Bus object paths, which only allow [A-Za-z0-9_] in each element,
+// so the YouTube id is hex encoded
+fn track_path(song_id: &str) -> String {
+    let mut path = String::from("/org/feather/track/");
+    for byte in song_id.bytes() {
+        let _ = write!(path, "{:02x}", byte);
+    }
+    path
+}
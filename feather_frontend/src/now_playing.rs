@@ -0,0 +1,103 @@
+use crate::debug_log;
+use feather::backend::Backend;
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1); // How often the pause state is checked
+const POSITION_INTERVAL: Duration = Duration::from_secs(5); // How often the position alone is rewritten
+
+// Keeps a JSON summary of the current song in the file named by FEATHER_NOW_PLAYING_FILE,
+// for status bars such as waybar or polybar to read. It is reset to idle on exit.
+pub struct NowPlayingFile {
+    path: PathBuf,        // File the summary is written to
+    task: JoinHandle<()>, // Follows the player until the app exits
+}
+
+impl NowPlayingFile {
+    pub fn start(backend: Arc<Backend>) -> Option<Self> {
+        let path = PathBuf::from(env::var_os("FEATHER_NOW_PLAYING_FILE")?);
+        if path.as_os_str().is_empty() {
+            return None;
+        }
+        Some(Self {
+            task: tokio::spawn(run(backend, path.clone())),
+            path,
+        })
+    }
+}
+
+impl Drop for NowPlayingFile {
+    fn drop(&mut self) {
+        self.task.abort();
+        // Otherwise the bar keeps showing the last song after Feather quits
+        if let Err(e) = write_atomic(&self.path, &idle()) {
+            debug_log::log(&format!("now playing: {}", e));
+        }
+    }
+}
+
+async fn run(backend: Arc<Backend>, path: PathBuf) {
+    let mut songs = backend.subscribe();
+    let mut poll = interval(POLL_INTERVAL);
+    let mut last: Option<(Value, Instant)> = None; // State written last, without the position
+    loop {
+        tokio::select! {
+            changed = songs.changed() => {
+                if changed.is_err() {
+                    return; // Backend is gone
+                }
+            }
+            _ = poll.tick() => (),
+        }
+        let summary = summary(&backend);
+        let mut state = summary.clone();
+        if let Some(fields) = state.as_object_mut() {
+            fields.remove("position");
+        }
+        // Song and pause changes are written right away, the position every few seconds
+        let due = last
+            .as_ref()
+            .is_none_or(|(written, time)| *written != state || time.elapsed() >= POSITION_INTERVAL);
+        if !due {
+            continue;
+        }
+        if let Err(e) = write_atomic(&path, &summary) {
+            debug_log::log(&format!("now playing: {}", e));
+        }
+        last = Some((state, Instant::now()));
+    }
+}
+
+fn idle() -> Value {
+    json!({ "state": "idle" })
+}
+
+// Describes what is playing: title, artists, position and duration in seconds, and state
+fn summary(backend: &Backend) -> Value {
+    let song = backend.song.lock().ok().and_then(|song| song.clone());
+    let Some(song) = song.filter(|_| !backend.player.is_idle()) else {
+        return idle();
+    };
+    let paused = !backend.player.is_playing().unwrap_or(false);
+    json!({
+        "title": song.song_name,
+        "artists": song.artist_name,
+        "position": backend.player.position_secs().round(),
+        "duration": backend.player.duration_secs().round(),
+        "state": if paused { "paused" } else { "playing" },
+    })
+}
+
+// Writes next to the file and renames it over, so readers never see half a summary
+fn write_atomic(path: &Path, summary: &Value) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, summary.to_string())?;
+    fs::rename(&temp, path)
+}
@@ -0,0 +1,43 @@
+use std::env;
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Opens a URL in the browser without blocking the render loop.
+// FEATHER_BROWSER overrides the system opener, e.g. "firefox --new-tab".
+pub fn open_url(url: &str) -> Result<(), String> {
+    let mut command = match env::var("FEATHER_BROWSER") {
+        Ok(browser) if !browser.trim().is_empty() => {
+            let mut parts = browser.split_whitespace();
+            let mut command = Command::new(parts.next().unwrap_or_default());
+            command.args(parts);
+            command
+        }
+        _ => system_opener(),
+    };
+
+    // Detach from the raw-mode terminal so the browser cannot draw over the TUI
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+
+    // Reap the opener in the background so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// Returns the platform's default URL opener
+fn system_opener() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    }
+}
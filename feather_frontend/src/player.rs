@@ -1,13 +1,28 @@
-use crate::backend::{Backend, Song};
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::prelude::{Alignment, Buffer, Rect};
+use crate::art::AlbumArt;
+use crate::redraw::Redraw;
+use crate::status_bar::{ERROR_DURATION, StatusBar};
+use crate::text::truncate_to_width;
+use crate::theme::{accent_style, border_style, muted_style};
+use crate::{clipboard, opener};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::{execute, terminal::SetTitle};
+use feather::SongId;
+use feather::backend::{Backend, BackendError, PlayerEvent, Song};
+use ratatui::prelude::{Alignment, Buffer, Position, Rect};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+use ratatui::widgets::{Block, Borders, LineGauge, Paragraph, Widget};
+use std::env;
+use std::io::stdout;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use tokio::sync::mpsc;
-use tokio::task;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+pub const COMPACT_WIDTH: u16 = 60; // Columns below which the player is a single line
+const MIN_TEXT_WIDTH: u16 = 30; // Columns kept for the title and progress bar beside album art
+const SEEK_THROTTLE: Duration = Duration::from_millis(150); // Min gap between seeks while dragging
+const VOLUME_WIDTH: u16 = 16; // Columns of the volume gauge beside the progress bar, label included
+const VOLUME_LABEL: &str = "Vol ";
 
 #[derive(PartialEq, PartialOrd, Debug)]
 enum SongState {
@@ -17,6 +32,13 @@ enum SongState {
     ErrorPlayingoSong, // An error occurred while playing the song
 }
 
+// The gauges a mouse drag can move
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Gauge {
+    Progress, // Seeks through the song
+    Volume,   // Sets mpv's volume
+}
+
 #[derive(Clone)]
 pub struct SongDetails {
     song: Song,             // Information about the song
@@ -28,16 +50,36 @@ pub struct SongPlayer {
     backend: Arc<Backend>,            // Backend reference for controlling playback
     songstate: Arc<Mutex<SongState>>, // Current state of the player (Idle, Playing, etc.)
     song_playing: Arc<Mutex<Option<SongDetails>>>, // Details of the currently playing song
-    rx: mpsc::Receiver<bool>,         // Receiver to listen for playback events
+    events: broadcast::Receiver<PlayerEvent>, // Playback events from the backend
+    gauge_area: Option<Rect>,         // Where the progress bar was last rendered
+    volume_area: Option<Rect>,        // Where the volume gauge was last rendered
+    dragging: Option<Gauge>,          // Gauge being dragged, if any
+    last_seek: Option<Instant>,       // When the last drag seek was sent to mpv
+    status: StatusBar,                // Confirmations for copy and open
+    redraw: Redraw,                   // Wakes the render loop on state changes
+    set_title: bool,                  // Whether the terminal title follows the song
+    title_song: Option<SongId>,       // Song the terminal title was last set for
+    art: Option<AlbumArt>,            // Thumbnail beside the title, when enabled
 }
 
 impl SongPlayer {
-    pub fn new(backend: Arc<Backend>, rx: mpsc::Receiver<bool>) -> Self {
+    pub fn new(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Self {
+        let art = AlbumArt::new(Arc::clone(&backend), redraw.clone());
+        let events = backend.events();
         let player = Self {
             backend,
             songstate: Arc::new(Mutex::new(SongState::Idle)),
             song_playing: Arc::new(Mutex::new(None)),
-            rx,
+            events,
+            gauge_area: None,
+            volume_area: None,
+            dragging: None,
+            last_seek: None,
+            status,
+            redraw,
+            set_title: env::var("FEATHER_TERMINAL_TITLE").as_deref() == Ok("1"),
+            title_song: None,
+            art,
         };
         player.observe_time(); // Start observing playback time
         player
@@ -48,10 +90,10 @@ impl SongPlayer {
         let backend = Arc::clone(&self.backend);
         let song_playing = Arc::clone(&self.song_playing);
 
-        tokio::task::spawn(async move {
+        self.backend.spawn_until_shutdown(async move {
             loop {
                 // Try to get the current playback position from MPV
-                match backend.player.player.get_property::<f64>("time-pos") {
+                match backend.player.position() {
                     Ok(time) => {
                         // Lock the song_playing mutex and update the current playback time
                         if let Ok(mut song_lock) = song_playing.lock() {
@@ -68,26 +110,132 @@ impl SongPlayer {
         });
     }
 
-    // Handle key presses for playback control
-    pub fn handle_keystrokes(&mut self, key: KeyEvent) {
-        if let Ok(state) = self.songstate.lock() {
-            if *state == SongState::Playing {
-                match key.code {
-                    KeyCode::Char(' ') | KeyCode::Char(';') => {
-                        // Toggle play/pause
-                        if let Ok(_) = self.backend.player.play_pause() {};
-                    }
-                    KeyCode::Right | KeyCode::Char('l') => {
-                        // Seek forward
-                        self.backend.player.seek_forward().ok();
-                    }
-                    KeyCode::Left | KeyCode::Char('j') => {
-                        // Seek backward
-                        self.backend.player.seek_backword().ok();
-                    }
-                    _ => (),
+    // Whether a song is currently playing
+    pub fn is_playing(&self) -> bool {
+        self.songstate
+            .lock()
+            .is_ok_and(|state| *state == SongState::Playing)
+    }
+
+    // Points the terminal title at the playing song, or back to "Feather" once playback stops
+    fn sync_title(&mut self) {
+        let song = if self.is_playing() {
+            self.backend.song.lock().ok().and_then(|song| song.clone())
+        } else {
+            None
+        };
+        let song_id = song.as_ref().map(|song| song.song_id.clone());
+        if song_id == self.title_song {
+            return; // Only write the escape on changes
+        }
+        let title = match &song {
+            Some(song) => format!("Feather — {}", song.song_name),
+            None => "Feather".to_string(),
+        };
+        // Written straight to the terminal, the title is not part of the ratatui buffer
+        let _ = execute!(stdout(), SetTitle(title));
+        self.title_song = song_id;
+    }
+
+    // Handle key presses for playback control, returning whether the key was used.
+    // Without a playing song nothing is used, so the key falls through to global bindings.
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
+        if !self.is_playing() {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(' ') | KeyCode::Char(';') => {
+                // Toggle play/pause
+                if let Ok(_) = self.backend.player.play_pause() {};
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                // Seek forward
+                self.backend.player.seek_forward().ok();
+            }
+            KeyCode::Left | KeyCode::Char('j') => {
+                // Seek backward
+                self.backend.player.seek_backword().ok();
+            }
+            KeyCode::Char('y') => {
+                // Copy the current song's URL
+                if let Ok(song) = self.backend.song.lock()
+                    && let Some(song) = song.as_ref()
+                {
+                    self.status
+                        .report(clipboard::copy(&song.url()), "Copied URL to clipboard");
+                }
+            }
+            KeyCode::Char('o') => {
+                // Open the current song in the browser
+                if let Ok(song) = self.backend.song.lock()
+                    && let Some(song) = song.as_ref()
+                {
+                    self.status
+                        .report(opener::open_url(&song.url()), "Opened in browser");
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // Seeks when the progress bar is clicked or dragged and sets the volume when its gauge is,
+    // returning whether the event was used
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        let position = Position::new(mouse.column, mouse.row);
+        let hit = |area: Option<Rect>| area.is_some_and(|area| area.contains(position));
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let gauge = if hit(self.gauge_area) {
+                    Gauge::Progress
+                } else if hit(self.volume_area) {
+                    Gauge::Volume
+                } else {
+                    return false;
                 };
+                self.dragging = Some(gauge);
+                self.drag_to(gauge, mouse.column, true);
+                true
+            }
+            MouseEventKind::Drag(MouseButton::Left) => match self.dragging {
+                Some(gauge) => {
+                    self.drag_to(gauge, mouse.column, false);
+                    true
+                }
+                None => false,
+            },
+            MouseEventKind::Up(MouseButton::Left) => match self.dragging.take() {
+                Some(gauge) => {
+                    // Always land on the final position, even if the last drag was throttled
+                    self.drag_to(gauge, mouse.column, true);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    // Moves a gauge to the fraction matching a column on it. Seeks while dragging are
+    // throttled so a fast drag doesn't flood mpv, unless `exact` asks for this position.
+    fn drag_to(&mut self, gauge: Gauge, column: u16, exact: bool) {
+        match (gauge, self.gauge_area, self.volume_area) {
+            (Gauge::Progress, Some(area), _)
+                if exact
+                    || self
+                        .last_seek
+                        .is_none_or(|time| time.elapsed() >= SEEK_THROTTLE) =>
+            {
+                let percent = column_ratio(area, column) * 100.0;
+                self.backend.player.seek_percent(percent).ok();
+                self.last_seek = Some(Instant::now());
+            }
+            (Gauge::Volume, _, Some(area)) => {
+                let volume = (column_ratio(area, column) * 100.0).round() as u8;
+                self.backend.player.set_volume(volume).ok();
+                self.redraw.request();
             }
+            _ => (), // Throttled, or the gauge is gone, e.g. the song ended mid-drag
         }
     }
 
@@ -96,8 +244,9 @@ impl SongPlayer {
         let songstate = Arc::clone(&self.songstate);
         let backend = Arc::clone(&self.backend);
         let song_playing = Arc::clone(&self.song_playing);
+        let redraw = self.redraw.clone();
 
-        task::spawn(async move {
+        self.backend.spawn_until_shutdown(async move {
             const MAX_IDLE_COUNT: i32 = 5; // Max checks before considering it an error
             let mut idle_count = 0;
 
@@ -111,21 +260,22 @@ impl SongPlayer {
                             if let Ok(mut song_lock) = song_playing.lock() {
                                 if let Ok(song) = backend.song.lock() {
                                     if let Some(value) = song.as_ref() {
-                                        let total_duration = backend
-                                            .player
-                                            .duration()
-                                            .parse::<f64>()
-                                            .map(|d| {
-                                                let total = d as i64;
-                                                format!("{:02}:{:02}", total / 60, total % 60)
-                                            })
-                                            .unwrap_or_default();
+                                        // mpv may not know the length yet right after loading
+                                        let secs = backend.player.duration_secs();
+                                        let total = if secs > 0.0 {
+                                            secs as i64
+                                        } else {
+                                            value.duration_secs.unwrap_or(0) as i64
+                                        };
+                                        let total_duration =
+                                            format!("{:02}:{:02}", total / 60, total % 60);
                                         *song_lock = Some(SongDetails {
                                             song: value.clone(),
                                             current_time: backend.player.get_current_time(),
                                             total_duration,
                                         });
                                         *state = SongState::Playing;
+                                        redraw.request();
                                         return; // Exit once playing is confirmed
                                     }
                                 }
@@ -138,6 +288,7 @@ impl SongPlayer {
                         if let Ok(mut state) = songstate.lock() {
                             *state = SongState::Idle;
                         }
+                        redraw.request();
                         idle_count += 1;
                     }
                     Err(_) => idle_count += 1, // Increase idle count if an error occurs
@@ -148,6 +299,7 @@ impl SongPlayer {
                     if let Ok(mut state) = songstate.lock() {
                         if *state == SongState::Loading {
                             *state = SongState::ErrorPlayingoSong;
+                            redraw.request();
                         }
                     }
                 }
@@ -156,19 +308,98 @@ impl SongPlayer {
         });
     }
 
-    // Render the player UI
-    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        // Check for playback event signals
-        if self.rx.try_recv().is_ok() {
-            if let Ok(mut state) = self.songstate.lock() {
-                *state = SongState::Loading;
+    // Renders the player as one line, "▶ Title 01:23/03:45", for narrow or short terminals
+    fn render_compact(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        self.gauge_area = None;
+        self.volume_area = None;
+        let text = match self.songstate.lock().as_deref() {
+            Ok(SongState::Playing) => match self.song_playing.lock().as_deref() {
+                Ok(Some(song)) => {
+                    let time = format!(" {}/{}", clock(&song.current_time), song.total_duration);
+                    // The title gives way first so the time stays visible
+                    let title_width = (area.width as usize).saturating_sub(2 + time.len());
+                    format!(
+                        "▶ {}{}",
+                        truncate_to_width(&song.song.song_name, title_width),
+                        time
+                    )
+                }
+                _ => "Loading...".to_string(),
+            },
+            Ok(SongState::Loading) => "Loading Song".to_string(),
+            Ok(SongState::ErrorPlayingoSong) => "Error Playing Song".to_string(),
+            _ => "No song is playing".to_string(),
+        };
+        Paragraph::new(truncate_to_width(&text, area.width as usize))
+            .style(border_style(focused))
+            .render(area, buf);
+    }
+
+    // Draws the album art at the left of the player and narrows the area left for the text.
+    // Nothing is drawn until the thumbnail is ready, or when it would crowd out the title.
+    fn render_art(&self, inner: &mut Rect, buf: &mut Buffer) {
+        let Some(art) = &self.art else {
+            return;
+        };
+        if !self.is_playing() {
+            return;
+        }
+        let Some(song_id) = self
+            .song_playing
+            .lock()
+            .ok()
+            .and_then(|song| song.as_ref().map(|song| song.song.song_id.clone()))
+        else {
+            return;
+        };
+        let width = art.width(&song_id, inner.height);
+        if width == 0 || inner.width < width + 1 + MIN_TEXT_WIDTH {
+            return;
+        }
+        art.render(&song_id, Rect { width, ..*inner }, buf);
+        inner.x += width + 1;
+        inner.width -= width + 1;
+    }
+
+    // Moves the player state along with the backend's playback events
+    fn apply_events(&mut self) {
+        loop {
+            let state = match self.events.try_recv() {
+                Ok(PlayerEvent::SongStarted(_)) => {
+                    self.check_playing(); // Waits for mpv to start playing it
+                    SongState::Loading
+                }
+                Ok(PlayerEvent::SongEnded) => SongState::Idle,
+                // The view that asked for the song reports why
+                Ok(PlayerEvent::Error(_)) => SongState::ErrorPlayingoSong,
+                Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => return,
+            };
+            if let Ok(mut songstate) = self.songstate.lock() {
+                *songstate = state;
             }
-            self.check_playing(); // Start checking for playback status
+        }
+    }
+
+    // Render the player UI, highlighting its border when focused
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        self.apply_events();
+
+        if self.set_title {
+            self.sync_title();
+        }
+
+        if area.width < COMPACT_WIDTH || area.height < 3 {
+            self.render_compact(area, buf, focused);
+            return;
         }
 
-        let block = Block::default().borders(Borders::ALL);
-        let inner = block.inner(area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style(focused));
+        let mut inner = block.inner(area);
         block.render(area, buf);
+        self.render_art(&mut inner, buf);
 
         if let Ok(state) = self.songstate.lock() {
             let text = match *state {
@@ -178,14 +409,13 @@ impl SongPlayer {
                         song_playing.as_ref().map_or_else(
                             || vec![Line::from("Loading...")],
                             |song| {
-                                let current_time = song
-                                    .current_time
-                                    .parse::<i64>()
-                                    .map(|t| format!("{:02}:{:02}", t / 60, t % 60))
-                                    .unwrap_or_default();
+                                let current_time = clock(&song.current_time);
                                 vec![
                                     Line::from(Span::styled(
-                                        song.song.song_name.clone(),
+                                        truncate_to_width(
+                                            &song.song.song_name,
+                                            inner.width as usize,
+                                        ),
                                         Style::default().add_modifier(Modifier::BOLD),
                                     )),
                                     Line::from(format!("{}/{}", current_time, song.total_duration)),
@@ -206,6 +436,157 @@ impl SongPlayer {
             Paragraph::new(text)
                 .alignment(Alignment::Center)
                 .render(inner, buf);
+
+            // Render the progress bar under the title and time when there is room for it,
+            // with the volume gauge to its right on wide panes
+            self.gauge_area = None;
+            self.volume_area = None;
+            if *state == SongState::Playing && inner.height >= 3 && inner.width > 2 {
+                let mut gauge_area = Rect {
+                    x: inner.x + 1,
+                    y: inner.y + 2,
+                    width: inner.width.saturating_sub(2),
+                    height: 1,
+                };
+                if gauge_area.width >= VOLUME_WIDTH * 3 {
+                    gauge_area.width -= VOLUME_WIDTH + 1;
+                    let volume_area = Rect {
+                        x: gauge_area.right() + 1,
+                        width: VOLUME_WIDTH,
+                        ..gauge_area
+                    };
+                    self.volume_area = Some(render_volume(&self.backend, volume_area, buf));
+                }
+                let position = self.backend.player.position_secs();
+                let duration = self.backend.player.duration_secs();
+                let ratio = if duration > 0.0 {
+                    (position / duration).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                LineGauge::default()
+                    .filled_style(accent_style())
+                    .unfilled_style(muted_style())
+                    .label("")
+                    .ratio(ratio)
+                    .render(gauge_area, buf);
+                self.gauge_area = Some(gauge_area);
+            }
+        }
+    }
+}
+
+// Draws the volume gauge after its label, returning where the gauge itself went
+fn render_volume(backend: &Backend, area: Rect, buf: &mut Buffer) -> Rect {
+    let label_width = VOLUME_LABEL.len() as u16;
+    let volume = backend.player.volume().unwrap_or(0);
+    Paragraph::new(VOLUME_LABEL).style(muted_style()).render(
+        Rect {
+            width: label_width,
+            ..area
+        },
+        buf,
+    );
+    let gauge = Rect {
+        x: area.x + label_width,
+        width: area.width - label_width,
+        ..area
+    };
+    LineGauge::default()
+        .filled_style(accent_style())
+        .unfilled_style(muted_style())
+        .label("")
+        .ratio(f64::from(volume) / 100.0)
+        .render(gauge, buf);
+    gauge
+}
+
+// Fraction of a one-line gauge left of a column, clamped to its ends
+fn column_ratio(gauge: Rect, column: u16) -> f64 {
+    let offset = column.clamp(gauge.x, gauge.x + gauge.width.saturating_sub(1)) - gauge.x;
+    offset as f64 / gauge.width.saturating_sub(1).max(1) as f64
+}
+
+// Plays a song in the background, as every list does on Enter, reporting failures in the
+// status bar. The player follows the backend's events from there.
+pub fn spawn_play(backend: Arc<Backend>, song: Song, status: StatusBar, redraw: Redraw) {
+    tokio::spawn(async move {
+        match backend.play_music(song).await {
+            Ok(()) | Err(BackendError::Superseded) => (), // Superseded: a newer song won
+            Err(e) => status.set_error(e.to_string(), ERROR_DURATION),
         }
+        redraw.request();
+    });
+}
+
+// Formats a playback position in seconds as MM:SS
+fn clock(seconds: &str) -> String {
+    seconds
+        .parse::<i64>()
+        .map(|t| format!("{:02}:{:02}", t / 60, t % 60))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather::testing::TestBackend;
+
+    // A player showing the given song 83 seconds in
+    fn playing(test: &TestBackend, song_name: &str) -> SongPlayer {
+        let player = SongPlayer::new(test.backend.clone(), StatusBar::new(), Redraw::new());
+        *player.songstate.lock().unwrap() = SongState::Playing;
+        *player.song_playing.lock().unwrap() = Some(SongDetails {
+            song: Song::new(song_name.to_string(), "id".to_string(), Vec::new()),
+            current_time: "83".to_string(),
+            total_duration: "03:45".to_string(),
+        });
+        player
+    }
+
+    fn render(player: &mut SongPlayer, width: u16, height: u16) -> Vec<String> {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+        player.render(buf.area, &mut buf, false);
+        (0..height)
+            .map(|y| (0..width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn narrow_player_is_one_line() {
+        let test = TestBackend::new();
+        let mut player = playing(&test, "A Song");
+        let rows = render(&mut player, 40, 3);
+        assert_eq!(rows[0].trim_end(), "▶ A Song 01:23/03:45");
+        assert!(rows[1].trim().is_empty());
+        assert!(player.gauge_area.is_none()); // Nothing to drag
+    }
+
+    #[tokio::test]
+    async fn narrow_player_shortens_the_title_before_the_time() {
+        let test = TestBackend::new();
+        let mut player = playing(&test, "A Very Long Title");
+        let rows = render(&mut player, 20, 1);
+        assert_eq!(rows[0], "▶ A Ver… 01:23/03:45");
+    }
+
+    #[tokio::test]
+    async fn short_player_is_one_line() {
+        let test = TestBackend::new();
+        let mut player = playing(&test, "A Song");
+        let rows = render(&mut player, COMPACT_WIDTH + 20, 2);
+        assert!(rows[0].starts_with("▶ A Song 01:23/03:45"));
+    }
+
+    #[tokio::test]
+    async fn wide_player_has_a_border_and_gauges() {
+        let test = TestBackend::new();
+        let mut player = playing(&test, "A Song");
+        let rows = render(&mut player, COMPACT_WIDTH + 20, 5);
+        assert!(rows[0].starts_with('┌'));
+        assert!(rows[1].contains("A Song"));
+        assert!(rows[2].contains("01:23/03:45"));
+        assert!(player.gauge_area.is_some());
+        assert!(player.volume_area.is_some());
     }
 }
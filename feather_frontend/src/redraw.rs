@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+// Wakes the render loop when a background task changed something on screen.
+// Clones share one notifier, so every component can hold its own handle.
+#[derive(Clone, Default)]
+pub struct Redraw {
+    notify: Arc<Notify>,
+}
+
+impl Redraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Asks for a new frame; requests made while a frame is drawn are kept for the next wait
+    pub fn request(&self) {
+        self.notify.notify_one();
+    }
+
+    // Resolves once a new frame has been requested
+    pub async fn requested(&self) {
+        self.notify.notified().await;
+    }
+}
@@ -0,0 +1,390 @@
+use crate::debug_log;
+use feather::backend::{Backend, Song};
+use feather::database::{Listen, ScrobbleQueue};
+use feather::proxy;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+const LISTENBRAINZ_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+const LASTFM_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+const POLL_INTERVAL: Duration = Duration::from_secs(5); // How often playback progress is checked
+const RETRY_INTERVAL: Duration = Duration::from_secs(60); // How often queued listens are retried
+const MIN_LENGTH: f64 = 30.0; // Shorter songs are never scrobbled
+const MAX_WAIT: f64 = 240.0; // Songs count as listened after half their length or 4 minutes
+const BATCH: usize = 50; // Queued listens submitted per retry
+
+// Where listens are sent, with the credentials each service needs
+enum Service {
+    ListenBrainz {
+        token: String, // User token from listenbrainz.org/settings
+    },
+    LastFm {
+        api_key: String,     // Key of a Last.fm API account
+        api_secret: String,  // Secret signing each call
+        session_key: String, // Session of the user being scrobbled for
+    },
+}
+
+impl Service {
+    // Reads the service from FEATHER_SCROBBLE and its credentials from the environment
+    fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        match var("FEATHER_SCROBBLE")?.trim().to_lowercase().as_str() {
+            "listenbrainz" => Some(Service::ListenBrainz {
+                token: var("FEATHER_LISTENBRAINZ_TOKEN")?,
+            }),
+            "lastfm" => Some(Service::LastFm {
+                api_key: var("FEATHER_LASTFM_API_KEY")?,
+                api_secret: var("FEATHER_LASTFM_API_SECRET")?,
+                session_key: var("FEATHER_LASTFM_SESSION_KEY")?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Submits "now playing" updates and finished listens in the background.
+// Listens that cannot be sent are queued in the history database and retried later,
+// so network trouble never reaches playback or the UI.
+pub struct Scrobbler {
+    task: JoinHandle<()>, // Follows the player until the app exits
+}
+
+impl Scrobbler {
+    // Starts scrobbling when FEATHER_SCROBBLE names a service with complete credentials
+    pub fn start(backend: Arc<Backend>) -> Option<Self> {
+        let service = Service::from_env()?;
+        let queue = match backend.history.scrobble_queue() {
+            Ok(queue) => queue,
+            Err(e) => {
+                debug_log::log(&format!("scrobble: cannot open queue: {}", e));
+                return None;
+            }
+        };
+        let client = Client {
+            http: proxy::client_builder().build().unwrap_or_default(),
+            service: Arc::new(service),
+        };
+        Some(Self {
+            task: tokio::spawn(run(backend, client, queue)),
+        })
+    }
+}
+
+impl Drop for Scrobbler {
+    fn drop(&mut self) {
+        self.task.abort(); // Anything unsent is already in the queue
+    }
+}
+
+// The song being listened to and whether it has been counted yet
+struct Current {
+    song: Song,
+    started_at: u64,
+    scrobbled: bool,
+}
+
+async fn run(backend: Arc<Backend>, client: Client, queue: ScrobbleQueue) {
+    let mut songs = backend.subscribe();
+    songs.mark_unchanged(); // Only songs started from now on are scrobbled
+    let mut poll = interval(POLL_INTERVAL);
+    let mut retry = interval(RETRY_INTERVAL);
+    let mut current: Option<Current> = None;
+    loop {
+        tokio::select! {
+            changed = songs.changed() => {
+                if changed.is_err() {
+                    return; // Backend is gone
+                }
+                let song = songs.borrow_and_update().clone();
+                // Incognito plays are neither announced nor counted
+                current = song.filter(|_| !backend.is_incognito()).map(|song| {
+                    let client = client.clone();
+                    let playing = song.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = client.now_playing(&playing).await {
+                            debug_log::log(&format!("scrobble: now playing failed: {}", e));
+                        }
+                    });
+                    Current { song, started_at: unix_now(), scrobbled: false }
+                });
+            }
+            _ = poll.tick() => {
+                let Some(current) = current.as_mut().filter(|c| !c.scrobbled) else {
+                    continue;
+                };
+                if !listened(backend.player.duration_secs(), backend.player.position_secs()) {
+                    continue;
+                }
+                current.scrobbled = true;
+                let listen = Listen {
+                    song_name: current.song.song_name.clone(),
+                    song_id: current.song.song_id.clone(),
+                    artist_name: current.song.artist_name.clone(),
+                    listened_at: current.started_at,
+                };
+                if let Err(e) = queue.push(&listen) {
+                    debug_log::log(&format!("scrobble: cannot queue listen: {}", e));
+                }
+                flush(&client, &queue).await;
+            }
+            _ = retry.tick() => flush(&client, &queue).await,
+        }
+    }
+}
+
+// Whether a song of the given length, in seconds, counts as listened at the position
+fn listened(length: f64, position: f64) -> bool {
+    length >= MIN_LENGTH && position >= (length / 2.0).min(MAX_WAIT)
+}
+
+// Submits queued listens oldest first, stopping at the first failure to keep their order
+async fn flush(client: &Client, queue: &ScrobbleQueue) {
+    drain(
+        queue,
+        |listen| async move { client.scrobble(&listen).await },
+    )
+    .await;
+}
+
+// Hands queued listens to `submit` oldest first, removing each one it accepts
+async fn drain<F, Fut>(queue: &ScrobbleQueue, mut submit: F)
+where
+    F: FnMut(Listen) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let pending = match queue.pending(BATCH) {
+        Ok(pending) => pending,
+        Err(e) => {
+            debug_log::log(&format!("scrobble: cannot read queue: {}", e));
+            return;
+        }
+    };
+    for (key, listen) in pending {
+        if let Err(e) = submit(listen).await {
+            debug_log::log(&format!("scrobble: submit failed, will retry: {}", e));
+            return;
+        }
+        let _ = queue.remove(&key);
+    }
+}
+
+#[derive(Clone)]
+struct Client {
+    http: reqwest::Client,
+    service: Arc<Service>,
+}
+
+impl Client {
+    // Tells the service which song just started
+    async fn now_playing(&self, song: &Song) -> Result<(), String> {
+        match self.service.as_ref() {
+            Service::ListenBrainz { token } => {
+                let metadata = track_metadata(&song.song_name, &song.artist_name, &song.url());
+                let body = json!({
+                    "listen_type": "playing_now",
+                    "payload": [{ "track_metadata": metadata }],
+                });
+                self.listenbrainz(token, body).await
+            }
+            Service::LastFm { .. } => {
+                let artist = song.artist_name.join(", ");
+                self.lastfm(vec![
+                    ("method", "track.updateNowPlaying".to_string()),
+                    ("artist", artist),
+                    ("track", song.song_name.clone()),
+                ])
+                .await
+            }
+        }
+    }
+
+    // Submits a finished listen
+    async fn scrobble(&self, listen: &Listen) -> Result<(), String> {
+        match self.service.as_ref() {
+            Service::ListenBrainz { token } => {
+                let url = format!("https://youtube.com/watch?v={}", listen.song_id);
+                let metadata = track_metadata(&listen.song_name, &listen.artist_name, &url);
+                let body = json!({
+                    "listen_type": "single",
+                    "payload": [{ "listened_at": listen.listened_at, "track_metadata": metadata }],
+                });
+                self.listenbrainz(token, body).await
+            }
+            Service::LastFm { .. } => {
+                self.lastfm(vec![
+                    ("method", "track.scrobble".to_string()),
+                    ("artist", listen.artist_name.join(", ")),
+                    ("track", listen.song_name.clone()),
+                    ("timestamp", listen.listened_at.to_string()),
+                ])
+                .await
+            }
+        }
+    }
+
+    async fn listenbrainz(&self, token: &str, body: serde_json::Value) -> Result<(), String> {
+        self.http
+            .post(LISTENBRAINZ_URL)
+            .header("Authorization", format!("Token {}", token))
+            .json(&body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    // Signs and posts a Last.fm call, as described at last.fm/api/authspec
+    async fn lastfm(&self, mut params: Vec<(&str, String)>) -> Result<(), String> {
+        let Service::LastFm {
+            api_key,
+            api_secret,
+            session_key,
+        } = self.service.as_ref()
+        else {
+            return Err("Not a Last.fm client".to_string());
+        };
+        params.push(("api_key", api_key.clone()));
+        params.push(("sk", session_key.clone()));
+        params.sort_by(|a, b| a.0.cmp(b.0));
+        let mut signature: String = params.iter().map(|(k, v)| format!("{}{}", k, v)).collect();
+        signature.push_str(api_secret);
+        params.push(("api_sig", format!("{:x}", md5::compute(signature))));
+        params.push(("format", "json".to_string())); // Not part of the signature
+        self.http
+            .post(LASTFM_URL)
+            .form(&params)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ListenBrainz track description, pointing back at the YouTube video
+fn track_metadata(song_name: &str, artist_name: &[String], url: &str) -> serde_json::Value {
+    json!({
+        "artist_name": artist_name.join(", "),
+        "track_name": song_name,
+        "additional_info": {
+            "origin_url": url,
+            "submission_client": "Feather",
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather::database::HistoryDB;
+
+    fn listen(song_id: &str, listened_at: u64) -> Listen {
+        Listen {
+            song_name: format!("Song {}", song_id),
+            song_id: song_id.to_string(),
+            artist_name: Vec::new(),
+            listened_at,
+        }
+    }
+
+    fn queued(queue: &ScrobbleQueue) -> Vec<String> {
+        let pending = queue.pending(BATCH).unwrap();
+        pending
+            .into_iter()
+            .map(|(_, listen)| listen.song_id)
+            .collect()
+    }
+
+    #[test]
+    fn songs_count_after_half_their_length_or_four_minutes() {
+        let cases = [
+            (0.0, 0.0, false),     // Length not known yet
+            (29.0, 29.0, false),   // Too short to ever count
+            (30.0, 14.0, false),   // Shortest song that counts, before its half
+            (30.0, 15.0, true),    // ... and at it
+            (200.0, 99.0, false),  // Half of the song not reached
+            (200.0, 100.0, true),  // Half of the song reached
+            (600.0, 239.0, false), // Long song, before four minutes
+            (600.0, 240.0, true),  // Long song, after four minutes rather than half
+        ];
+        for (length, position, expected) in cases {
+            assert_eq!(
+                listened(length, position),
+                expected,
+                "{}s into a {}s song",
+                position,
+                length
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_listens_are_submitted_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = HistoryDB::new_at(dir.path()).unwrap();
+        let queue = history.scrobble_queue().unwrap();
+        for (id, listened_at) in [("b", 20), ("c", 30), ("a", 10)] {
+            queue.push(&listen(id, listened_at)).unwrap();
+        }
+
+        let mut sent = Vec::new();
+        drain(&queue, |listen| {
+            sent.push(listen.song_id);
+            async { Ok(()) }
+        })
+        .await;
+        assert_eq!(sent, ["a", "b", "c"]);
+        assert!(queued(&queue).is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_listens_stay_queued_for_the_next_retry() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = HistoryDB::new_at(dir.path()).unwrap();
+        let queue = history.scrobble_queue().unwrap();
+        for (id, listened_at) in [("a", 10), ("b", 20), ("c", 30)] {
+            queue.push(&listen(id, listened_at)).unwrap();
+        }
+
+        // The service goes away after the first listen
+        let mut sent = Vec::new();
+        drain(&queue, |listen| {
+            let offline = listen.song_id != "a";
+            if !offline {
+                sent.push(listen.song_id);
+            }
+            async move {
+                if offline {
+                    Err("offline".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+        assert_eq!(sent, ["a"]);
+        assert_eq!(queued(&queue), ["b", "c"]); // Kept in order, nothing skipped
+
+        drain(&queue, |listen| {
+            sent.push(listen.song_id);
+            async { Ok(()) }
+        })
+        .await;
+        assert_eq!(sent, ["a", "b", "c"]);
+        assert!(queued(&queue).is_empty());
+    }
+}
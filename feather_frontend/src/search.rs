@@ -1,22 +1,40 @@
-use crate::backend::{Backend, Song};
-use crossterm::event::{KeyCode, KeyEvent};
-use feather::{ArtistName, SongId, SongName};
+use crate::list_nav::ListNav;
+use crate::player::spawn_play;
+use crate::redraw::Redraw;
+use crate::status_bar::StatusBar;
+use crate::text::truncate_to_width;
+use crate::theme::{NOW_PLAYING, border_style, error_style, selected_style, text_style};
+use crate::{clipboard, downloads, opener};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use feather::ArtistName;
+use feather::backend::{Backend, Song};
+use feather::yt::SearchResult;
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
+    layout::{Constraint, Layout, Position, Rect},
+    style::Style,
     text::Span,
     widgets::{
-        Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarState,
-        StatefulWidget, Widget,
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
     },
 };
-use std::sync::Arc;
+use std::{env, sync::Arc};
 use tokio::{
     sync::mpsc,
-    time::{Duration, sleep},
+    task::JoinHandle,
+    time::{Duration, Instant, interval, sleep},
 };
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
+
+const MAX_SUGGESTIONS: usize = 8; // Suggestions shown in the popup
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100); // Time per spinner frame
+
+// Search results delivered from the background search task
+struct SearchResponse {
+    play_first: bool, // Play the first result as soon as it arrives
+    result: Result<Vec<SearchResult>, String>,
+}
 
 // Defines possible states for the search interface
 enum SearchState {
@@ -25,77 +43,120 @@ enum SearchState {
 }
 
 pub struct Search<'a> {
-    textarea: TextArea<'a>, // Text input widget for search queries
-    state: SearchState,     // Current UI state
-    query: String,          // Current search query text
-    tx: mpsc::Sender<Result<Vec<((String, String), Vec<String>)>, String>>, // Sender for search results
-    rx: mpsc::Receiver<Result<Vec<((String, String), Vec<String>)>, String>>, // Receiver for search results
-    tx_player: mpsc::Sender<bool>, // Channel to communicate with player
-    backend: Arc<Backend>,         // Audio backend for search and playback
-    vertical_scroll_state: ScrollbarState, // Vertical scrollbar state
-    display_content: bool,         // Flag to show search results
-    results: Result<Option<Vec<((SongName, SongId), Vec<ArtistName>)>>, String>, // Search results or error
-    selected: usize,             // Index of selected result
-    selected_song: Option<Song>, // Currently selected song details
-    max_len: Option<usize>,      // Total number of search results
+    textarea: TextArea<'a>,             // Text input widget for search queries
+    state: SearchState,                 // Current UI state
+    query: String,                      // Current search query text
+    tx: mpsc::Sender<SearchResponse>,   // Sender for search results
+    rx: mpsc::Receiver<SearchResponse>, // Receiver for search results
+    backend: Arc<Backend>,              // Audio backend for search and playback
+    display_content: bool,              // Flag to show search results
+    results: Result<Option<Vec<SearchResult>>, String>, // Search results or error
+    nav: ListNav,                       // Selection within the results
+    selected_song: Option<Song>,        // Currently selected song details
+    bar_area: Rect,                     // Area the search bar was last rendered into
+    results_title: String,              // Title of the results block
+    deprioritized: Vec<String>,         // Title keywords pushed to the bottom of the results
+    rerank: bool,                       // Whether the keyword re-ranking is applied
+    suggestions_enabled: bool,          // Whether suggestions are fetched while typing
+    suggestions: Vec<String>,           // Suggestions for the text in the search bar
+    suggestion_selected: Option<usize>, // Highlighted suggestion, if any
+    suggestion_query: String,           // Text the current suggestions were requested for
+    suggestion_task: Option<JoinHandle<()>>, // Pending suggestion lookup
+    tx_suggestions: mpsc::Sender<(String, Vec<String>)>, // Sender for suggestions
+    rx_suggestions: mpsc::Receiver<(String, Vec<String>)>, // Receiver for suggestions
+    status: StatusBar,                  // Action confirmations and playback errors
+    redraw: Redraw,                     // Wakes the render loop when a task finishes
+    search_task: Option<JoinHandle<()>>, // Search still in flight
+    searching: Option<(String, Instant)>, // Query being searched and when it started
 }
 
 impl Search<'_> {
     // Constructor initializing the Search struct
-    pub fn new(backend: Arc<Backend>, tx_player: mpsc::Sender<bool>) -> Self {
+    pub fn new(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Self {
         let (tx, rx) = mpsc::channel(32); // Create channel for async search results
+        let (tx_suggestions, rx_suggestions) = mpsc::channel(8);
+        // Suggestions cost a request per pause in typing, allow metered connections to opt out
+        let suggestions_enabled = env::var("FEATHER_SEARCH_SUGGESTIONS").as_deref() != Ok("0");
         Self {
             query: String::new(),
             state: SearchState::SearchBar,
             textarea: TextArea::default(),
             tx,
             rx,
-            tx_player,
             backend,
-            vertical_scroll_state: ScrollbarState::default(),
             display_content: false,
             results: Ok(None),
-            selected: 0,
+            nav: ListNav::new(),
             selected_song: None,
-            max_len: None,
+            bar_area: Rect::default(),
+            results_title: "Results".to_string(),
+            deprioritized: deprioritized_keywords(),
+            rerank: true,
+            suggestions_enabled,
+            suggestions: Vec::new(),
+            suggestion_selected: None,
+            suggestion_query: String::new(),
+            suggestion_task: None,
+            tx_suggestions,
+            rx_suggestions,
+            status,
+            redraw,
+            search_task: None,
+            searching: None,
         }
     }
 
-    // Handles keyboard input based on current state
-    pub fn handle_keystrokes(&mut self, key: KeyEvent) {
+    // Handles keyboard input based on current state, returning whether the key was used.
+    // The search bar takes every key as text, the results leave unknown keys to global bindings.
+    pub fn handle_keystrokes(&mut self, key: KeyEvent) -> bool {
         if let SearchState::SearchBar = self.state {
             match key.code {
+                KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.rerun_search();
+                }
                 KeyCode::Tab => {
-                    // Switch to results state
-                    self.change_state();
+                    if self.suggestion_selected.is_some() {
+                        // Accept the highlighted suggestion
+                        self.accept_suggestion();
+                    } else {
+                        // Switch to results state
+                        self.change_state();
+                    }
+                }
+                KeyCode::Down if !self.suggestions.is_empty() => {
+                    // Move suggestion highlight down
+                    let last = self.suggestions.len() - 1;
+                    self.suggestion_selected =
+                        Some(self.suggestion_selected.map_or(0, |i| (i + 1).min(last)));
+                }
+                KeyCode::Up if !self.suggestions.is_empty() => {
+                    // Move suggestion highlight up, leaving the popup at the top
+                    self.suggestion_selected = match self.suggestion_selected {
+                        Some(0) | None => None,
+                        Some(i) => Some(i - 1),
+                    };
+                }
+                KeyCode::Enter
+                    if key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    // "Lucky" search: play the first result without opening the list
+                    if self.suggestion_selected.is_some() {
+                        self.accept_suggestion();
+                    }
+                    self.submit_search(true);
                 }
                 KeyCode::Enter => {
-                    // Execute search
-                    self.display_content = false;
-                    self.selected = 0;
-                    let text = self.textarea.lines();
-                    if !text.is_empty() {
-                        self.query = text[0].trim().to_string();
-                        let tx = self.tx.clone();
-                        let query = self.query.clone();
-                        let backend = self.backend.clone();
-                        tokio::spawn(async move {
-                            // Async task for search
-                            sleep(Duration::from_millis(500)).await; // Debounce
-                            match backend.yt.search(&query).await {
-                                Ok(songs) => {
-                                    let _ = tx.send(Ok(songs)).await;
-                                }
-                                Err(e) => {
-                                    let _ = tx.send(Err(e)).await;
-                                }
-                            }
-                        });
+                    if self.suggestion_selected.is_some() {
+                        self.accept_suggestion();
                     }
+                    self.submit_search(false);
                 }
                 _ => {
-                    self.textarea.input(key);
-                } // Handle text input
+                    readline_input(&mut self.textarea, key); // Handle text input
+                    self.request_suggestions();
+                }
             }
         } else {
             // SearchResults state
@@ -103,33 +164,220 @@ impl Search<'_> {
                 KeyCode::Tab => {
                     self.change_state();
                 } // Switch to search bar
-                KeyCode::Char('j') | KeyCode::Down => {
-                    // Move selection down
-                    self.selected = self.selected.saturating_add(1);
-                    if let Some(len) = self.max_len {
-                        self.selected = self.selected.min(len - 1);
+                KeyCode::Char('r') => {
+                    // Toggle re-ranking for the current results
+                    self.rerank = !self.rerank;
+                    self.nav.first();
+                }
+                KeyCode::Char('R') => {
+                    self.rerun_search();
+                }
+                KeyCode::Char('D') => {
+                    // Save the selected song for offline playback
+                    if let Some(song) = self.selected_song.clone() {
+                        downloads::start_download(
+                            self.backend.clone(),
+                            song,
+                            self.status.clone(),
+                            self.redraw.clone(),
+                        );
+                    }
+                }
+                KeyCode::Char('y') => {
+                    // Copy the selected song's URL
+                    if let Some(song) = &self.selected_song {
+                        self.status
+                            .report(clipboard::copy(&song.url()), "Copied URL to clipboard");
                     }
-                    self.vertical_scroll_state = self.vertical_scroll_state.position(self.selected);
                 }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    // Move selection up
-                    self.selected = self.selected.saturating_sub(1);
-                    self.vertical_scroll_state = self.vertical_scroll_state.position(self.selected);
+                KeyCode::Char('o') => {
+                    // Open the selected song in the browser
+                    if let Some(song) = &self.selected_song {
+                        self.status
+                            .report(opener::open_url(&song.url()), "Opened in browser");
+                    }
+                }
+                KeyCode::Char('A') => {
+                    // Show more songs by the selected song's first artist
+                    if let Some(artist) = self
+                        .selected_song
+                        .as_ref()
+                        .and_then(|song| song.artist_name.first().cloned())
+                    {
+                        self.browse_artist(artist);
+                    }
                 }
                 KeyCode::Enter => {
                     // Play selected song
                     if let Some(song) = self.selected_song.clone() {
-                        let backend = self.backend.clone();
-                        let tx_player = self.tx_player.clone();
-                        tokio::spawn(async move {
-                            let _ = backend.play_music(song).await.is_ok();
-                            let _ = tx_player.send(true).await;
-                        });
+                        self.play(song);
                     }
                 }
-                _ => {}
+                _ => return self.nav.handle_key(key), // List movement
             }
         }
+        true
+    }
+
+    // Count typed before the next list motion, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.nav.pending_count()
+    }
+
+    // Whether keys currently go to the search bar as text
+    pub fn is_typing(&self) -> bool {
+        matches!(self.state, SearchState::SearchBar)
+    }
+
+    // Plays a song in the background, reporting failures in the status bar
+    fn play(&self, song: Song) {
+        spawn_play(
+            self.backend.clone(),
+            song,
+            self.status.clone(),
+            self.redraw.clone(),
+        );
+    }
+
+    // Executes the search for the current text in the search bar
+    fn submit_search(&mut self, play_first: bool) {
+        self.clear_suggestions();
+        let text = self.textarea.lines();
+        if !text.is_empty() {
+            self.query = text[0].trim().to_string();
+            self.results_title = "Results".to_string();
+            self.spawn_search(self.query.clone(), play_first);
+        }
+    }
+
+    // Lists more songs by the given artist in the results view
+    pub fn browse_artist(&mut self, artist: ArtistName) {
+        self.clear_suggestions();
+        self.results_title = format!("Artist: {}", artist);
+        self.spawn_search(format!("{} songs", artist), false);
+        self.state = SearchState::SearchResults;
+    }
+
+    // Runs a search in the background and delivers the results to the render loop
+    fn spawn_search(&mut self, query: String, play_first: bool) {
+        if self
+            .searching
+            .as_ref()
+            .is_some_and(|(pending, _)| *pending == query)
+        {
+            return; // Already on its way, a second Enter would only duplicate the request
+        }
+        // A newer search replaces the pending one, along with any result it already sent
+        if let Some(task) = self.search_task.take() {
+            task.abort();
+        }
+        while self.rx.try_recv().is_ok() {}
+        self.searching = Some((query.clone(), Instant::now()));
+        self.display_content = false;
+        self.nav.first();
+        let tx = self.tx.clone();
+        let backend = self.backend.clone();
+        let redraw = self.redraw.clone();
+        self.search_task = Some(tokio::spawn(async move {
+            // Async task for search
+            let search = async {
+                sleep(Duration::from_millis(500)).await; // Debounce
+                backend.yt.search(&query).await.map_err(|e| e.to_string())
+            };
+            tokio::pin!(search);
+            let mut frames = interval(SPINNER_INTERVAL);
+            let result = loop {
+                tokio::select! {
+                    result = &mut search => break result,
+                    _ = frames.tick() => redraw.request(), // Animate the spinner
+                }
+            };
+            let _ = tx.send(SearchResponse { play_first, result }).await;
+            redraw.request();
+        }));
+    }
+
+    // Re-submits the most recent query and moves focus to the results list
+    fn rerun_search(&mut self) {
+        if self.query.is_empty() {
+            return;
+        }
+        self.textarea = TextArea::new(vec![self.query.clone()]);
+        self.textarea.move_cursor(CursorMove::End);
+        self.suggestion_query = self.query.clone();
+        self.submit_search(false);
+        self.state = SearchState::SearchResults;
+    }
+
+    // Schedules a debounced suggestion lookup for the text in the search bar,
+    // cancelling any lookup that is still pending
+    fn request_suggestions(&mut self) {
+        if !self.suggestions_enabled {
+            return;
+        }
+        let prefix = self.textarea.lines()[0].trim().to_string();
+        if prefix == self.suggestion_query {
+            return; // Cursor movement only, text unchanged
+        }
+        self.suggestion_query = prefix.clone();
+        if let Some(task) = self.suggestion_task.take() {
+            task.abort();
+        }
+        self.suggestion_selected = None;
+        if prefix.is_empty() {
+            self.suggestions.clear();
+            return;
+        }
+        let tx = self.tx_suggestions.clone();
+        let backend = self.backend.clone();
+        let redraw = self.redraw.clone();
+        self.suggestion_task = Some(tokio::spawn(async move {
+            sleep(Duration::from_millis(300)).await; // Debounce
+            // A failed lookup is dropped silently, it must never affect the real search
+            if let Ok(terms) = backend.yt.suggestions(&prefix).await {
+                let _ = tx.send((prefix, terms)).await;
+                redraw.request();
+            }
+        }));
+    }
+
+    // Replaces the search bar text with the highlighted suggestion
+    fn accept_suggestion(&mut self) {
+        if let Some(term) = self
+            .suggestion_selected
+            .and_then(|i| self.suggestions.get(i).cloned())
+        {
+            self.textarea = TextArea::new(vec![term.clone()]);
+            self.textarea.move_cursor(CursorMove::End);
+            self.suggestion_query = term;
+        }
+        self.clear_suggestions();
+    }
+
+    // Hides the suggestion popup and cancels any pending lookup
+    fn clear_suggestions(&mut self) {
+        if let Some(task) = self.suggestion_task.take() {
+            task.abort();
+        }
+        while self.rx_suggestions.try_recv().is_ok() {} // Drop lookups already in flight
+        self.suggestions.clear();
+        self.suggestion_selected = None;
+    }
+
+    // Focuses the part of the search view under a left click, selecting the clicked result.
+    // Returns whether a result row was hit.
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        if self.bar_area.contains(Position::new(column, row)) {
+            self.state = SearchState::SearchBar;
+            return false;
+        }
+        self.state = SearchState::SearchResults;
+        self.nav.click(column, row)
+    }
+
+    // Moves the results selection with the mouse wheel
+    pub fn scroll(&mut self, down: bool) {
+        self.nav.scroll(down)
     }
 
     // Toggles between search bar and results view
@@ -140,91 +388,304 @@ impl Search<'_> {
         }
     }
 
-    // Renders the search UI
-    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+    // Renders the search UI, highlighting the search bar or results when focused
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, focused: bool) {
+        let bar_focused = focused && self.is_typing();
+        let results_focused = focused && !self.is_typing();
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([
                 Constraint::Length(3), // Search bar height
                 Constraint::Min(0),    // Results area
-                Constraint::Length(3), // Bottom bar
             ])
             .split(area);
         let searchbar_area = chunks[0];
         let results_area = chunks[1];
-        let bottom_area = chunks[2];
+        self.bar_area = searchbar_area;
 
         // Check for new search results
         if let Ok(response) = self.rx.try_recv() {
-            if let Ok(result) = response {
-                self.results = Ok(Some(result));
-            } else if let Err(e) = response {
-                self.results = Err(e);
+            match response.result {
+                Ok(result) => {
+                    self.rerank = true; // Every new query starts out re-ranked
+                    if response.play_first {
+                        // Errors fall through to the normal error display instead
+                        let ranked = rank_results(result.clone(), &self.deprioritized);
+                        if let Some(first) = ranked.first() {
+                            self.play(song_of(first));
+                        }
+                    }
+                    self.results = Ok(Some(result));
+                }
+                Err(e) => self.results = Err(e),
             }
+            self.searching = None;
+            self.search_task = None;
             self.display_content = true;
         }
 
+        // Check for new suggestions, dropping any that no longer match the typed text
+        while let Ok((prefix, terms)) = self.rx_suggestions.try_recv() {
+            if prefix == self.suggestion_query {
+                self.suggestions = terms.into_iter().take(MAX_SUGGESTIONS).collect();
+                self.suggestion_selected = None;
+            }
+        }
+
         // Render search bar
-        let search_block = Block::default().title("Search Music").borders(Borders::ALL);
+        let search_block = Block::default()
+            .title("Search Music")
+            .borders(Borders::ALL)
+            .border_style(border_style(bar_focused));
         self.textarea.set_cursor_line_style(Style::default());
         self.textarea
             .set_placeholder_text("Search Song or Playlist");
-        self.textarea.set_style(Style::default().fg(Color::White));
+        self.textarea.set_style(text_style());
         self.textarea.set_block(search_block);
         self.textarea.render(searchbar_area, buf);
 
-        // Render vertical scrollbar
-        let vertical_scrollbar =
-            Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight)
-                .begin_symbol(Some("↑"))
-                .end_symbol(Some("↓"));
-        vertical_scrollbar.render(results_area, buf, &mut self.vertical_scroll_state);
-
         // Render search results if available
         if self.display_content {
             if let Ok(result) = self.results.clone() {
                 if let Some(r) = result {
-                    self.max_len = Some(r.len());
+                    let r = if self.rerank {
+                        rank_results(r, &self.deprioritized)
+                    } else {
+                        r
+                    };
+                    self.nav.set_len(r.len());
+                    self.nav.set_page_height(results_area.height);
+                    // Columns left for the text after borders and highlight symbol
+                    let row_width = results_area.width.saturating_sub(3) as usize;
+                    let playing = self.backend.current_song_id();
                     let items: Vec<ListItem> = r
                         .into_iter()
                         .enumerate()
-                        .map(|(i, ((song, songid), artists))| {
+                        .map(|(i, result)| {
                             // Format results
-                            let style = if i == self.selected {
-                                self.selected_song =
-                                    Some(Song::new(song.clone(), songid.clone(), artists.clone()));
-                                Style::default().fg(Color::Yellow).bg(Color::Blue)
+                            let style = if i == self.nav.selected() {
+                                self.selected_song = Some(song_of(&result));
+                                selected_style()
                             } else {
                                 Style::default()
                             };
-                            let text = format!("{} - {}", song, artists.join(", "));
-                            ListItem::new(Span::styled(text, style))
+                            let marker = if playing.as_ref() == Some(&result.song_id) {
+                                NOW_PLAYING
+                            } else {
+                                ""
+                            };
+                            let text = format!(
+                                "{}{} - {}",
+                                marker,
+                                result.song_name,
+                                result.artist_name.join(", ")
+                            );
+                            ListItem::new(Span::styled(truncate_to_width(&text, row_width), style))
                         })
                         .collect();
 
                     let mut list_state = ListState::default();
-                    list_state.select(Some(self.selected));
+                    list_state.select(Some(self.nav.selected()));
                     StatefulWidget::render(
                         // Render results list
                         List::new(items)
-                            .block(Block::default().title("Results").borders(Borders::ALL))
+                            .block(
+                                Block::default()
+                                    .title(self.results_title.as_str())
+                                    .borders(Borders::ALL)
+                                    .border_style(border_style(results_focused)),
+                            )
                             .highlight_symbol("▶"),
                         results_area,
                         buf,
                         &mut list_state,
                     );
+                    self.nav.set_area(results_area, list_state.offset());
+                    self.nav.render_scrollbar(results_area, buf);
                 }
             }
         }
 
-        // Render bottom help bar
-        let bottom_bar = Paragraph::new("Press '?' for Help in Global Mode")
-            .style(Style::default().fg(Color::White))
-            .block(Block::default().borders(Borders::ALL));
-        bottom_bar.render(bottom_area, buf); // Note: custom_area undefined, likely should be bottom_area
+        // While searching or after a failed search the results area shows a message instead
+        let results_block = Block::default()
+            .title(self.results_title.as_str())
+            .borders(Borders::ALL)
+            .border_style(border_style(results_focused));
+        if let Some((query, started)) = &self.searching {
+            // Spinner until the results arrive
+            let frame = (started.elapsed().as_millis() / SPINNER_INTERVAL.as_millis()) as usize;
+            Paragraph::new(format!(
+                "Searching \"{}\"… {}",
+                query,
+                SPINNER[frame % SPINNER.len()]
+            ))
+            .block(results_block)
+            .render(results_area, buf);
+        } else if let (true, Err(e)) = (self.display_content, &self.results) {
+            Paragraph::new(e.as_str())
+                .style(error_style())
+                .block(results_block)
+                .render(results_area, buf);
+        }
+
+        // Render suggestion popup over the results, directly under the search bar
+        if let SearchState::SearchBar = self.state
+            && !self.suggestions.is_empty()
+        {
+            let popup_area = Rect {
+                x: results_area.x,
+                y: results_area.y,
+                width: results_area.width,
+                height: (self.suggestions.len() as u16 + 2).min(results_area.height),
+            };
+            let items: Vec<ListItem> = self
+                .suggestions
+                .iter()
+                .enumerate()
+                .map(|(i, term)| {
+                    let style = if Some(i) == self.suggestion_selected {
+                        selected_style()
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(Span::styled(
+                        truncate_to_width(term, popup_area.width.saturating_sub(2) as usize),
+                        style,
+                    ))
+                })
+                .collect();
+            Clear.render(popup_area, buf);
+            Widget::render(
+                List::new(items).block(Block::default().title("Suggestions").borders(Borders::ALL)),
+                popup_area,
+                buf,
+            );
+        }
 
         // Render outer border
         let outer_block = Block::default().borders(Borders::ALL);
         outer_block.render(area, buf);
     }
 }
+
+// Translates readline-style chords before falling back to the textarea's own key handling
+pub fn readline_input(textarea: &mut TextArea, key: KeyEvent) {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('u') => {
+                // Clear everything before the cursor
+                textarea.delete_line_by_head();
+                return;
+            }
+            KeyCode::Char('w') => {
+                // Delete the word before the cursor
+                textarea.delete_word();
+                return;
+            }
+            KeyCode::Char('a') => {
+                textarea.move_cursor(CursorMove::Head);
+                return;
+            }
+            KeyCode::Char('e') => {
+                textarea.move_cursor(CursorMove::End);
+                return;
+            }
+            _ => (),
+        }
+    } else if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Backspace {
+        textarea.delete_word();
+        return;
+    }
+    textarea.input(key);
+}
+
+// Reads the comma separated keywords from FEATHER_DEPRIORITIZE, e.g. "live,cover,reaction"
+pub fn deprioritized_keywords() -> Vec<String> {
+    env::var("FEATHER_DEPRIORITIZE")
+        .unwrap_or_default()
+        .split(',')
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+// The song a search result stands for, keeping the details the search listed
+pub fn song_of(result: &SearchResult) -> Song {
+    Song::new(
+        result.song_name.clone(),
+        result.song_id.clone(),
+        result.artist_name.clone(),
+    )
+    .with_details(
+        result.metadata.duration.map(u64::from),
+        result.metadata.thumbnail.clone(),
+    )
+}
+
+// Moves results whose title contains one of the keywords as a whole word to the bottom,
+// keeping the original order within both groups and never dropping entries
+pub fn rank_results(mut results: Vec<SearchResult>, keywords: &[String]) -> Vec<SearchResult> {
+    if keywords.is_empty() {
+        return results;
+    }
+    results.sort_by_key(|result| {
+        result
+            .song_name
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| keywords.iter().any(|keyword| keyword == word))
+    });
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(titles: &[&str]) -> Vec<SearchResult> {
+        titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| SearchResult {
+                song_name: title.to_string(),
+                song_id: i.to_string(),
+                artist_name: Vec::new(),
+                metadata: Default::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rank_results_moves_keyword_titles_down() {
+        let keywords =
+            |words: &[&str]| -> Vec<String> { words.iter().map(|word| word.to_string()).collect() };
+        let cases: [(&[&str], &[&str], &[&str]); 6] = [
+            // No keywords keep the order
+            (&["Song (Live)", "Song"], &[], &["Song (Live)", "Song"]),
+            // Matched titles go last, both groups keep their order
+            (
+                &["A (Live)", "B", "C (Live)", "D"],
+                &["live"],
+                &["B", "D", "A (Live)", "C (Live)"],
+            ),
+            // Whole words only
+            (&["Alive", "Song"], &["live"], &["Alive", "Song"]),
+            // Any of several keywords, in any case
+            (
+                &["Cover - X", "Y", "Z [REACTION]"],
+                &["cover", "reaction"],
+                &["Y", "Cover - X", "Z [REACTION]"],
+            ),
+            // Everything matched still lists everything
+            (&["Live 1", "Live 2"], &["live"], &["Live 1", "Live 2"]),
+            (&[], &["live"], &[]),
+        ];
+        for (results, words, expected) in cases {
+            let ranked: Vec<String> = rank_results(titles(results), &keywords(words))
+                .into_iter()
+                .map(|result| result.song_name)
+                .collect();
+            assert_eq!(ranked, expected, "{:?} ranked against {:?}", results, words);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::debug_log;
+use crate::redraw::Redraw;
+use crate::status_bar::{MESSAGE_DURATION, StatusBar};
+use feather::backend::{Backend, Source};
+use feather::proxy;
+use serde::Deserialize;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+const API_URL: &str = "https://sponsor.ajay.app/api/skipSegments";
+const CATEGORIES: &str = r#"["music_offtopic","sponsor"]"#; // Segments worth skipping in songs
+const POLL_INTERVAL: Duration = Duration::from_millis(250); // How often the playhead is checked
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_SKIP: f64 = 1.0; // Shorter segments are not worth a jump
+
+// One submitted segment, as the API returns it
+#[derive(Deserialize)]
+struct Segment {
+    segment: [f64; 2], // Start and end in seconds
+    category: String,
+}
+
+// Skips sponsor reads and non-music parts of songs using SponsorBlock, enabled with
+// FEATHER_SPONSORBLOCK=1. Failed lookups are only logged, the song then plays in full.
+pub struct SponsorBlock {
+    task: JoinHandle<()>, // Follows the player until the app exits
+}
+
+impl SponsorBlock {
+    pub fn start(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Option<Self> {
+        if env::var("FEATHER_SPONSORBLOCK").as_deref() != Ok("1") {
+            return None;
+        }
+        let http = proxy::client_builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Some(Self {
+            task: tokio::spawn(run(backend, http, status, redraw)),
+        })
+    }
+}
+
+impl Drop for SponsorBlock {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run(backend: Arc<Backend>, http: reqwest::Client, status: StatusBar, redraw: Redraw) {
+    let mut songs = backend.subscribe();
+    let mut poll = interval(POLL_INTERVAL);
+    let mut segments: Vec<Segment> = Vec::new(); // Segments of the current song
+    let mut lookup: Option<JoinHandle<Vec<Segment>>> = None; // Fetch for the current song
+    loop {
+        tokio::select! {
+            changed = songs.changed() => {
+                if changed.is_err() {
+                    return; // Backend is gone
+                }
+                let song = songs.borrow_and_update().clone();
+                segments.clear();
+                if let Some(task) = lookup.take() {
+                    task.abort(); // Meant for the previous song
+                }
+                if let Some(song) = song.filter(|song| song.source() == Source::YouTube) {
+                    let http = http.clone();
+                    lookup = Some(tokio::spawn(async move {
+                        fetch_segments(&http, &song.song_id).await.unwrap_or_else(|e| {
+                            debug_log::log(&format!("sponsorblock: lookup failed: {}", e));
+                            Vec::new()
+                        })
+                    }));
+                }
+            }
+            _ = poll.tick() => {
+                if let Some(task) = lookup.take_if(|task| task.is_finished()) {
+                    segments = task.await.unwrap_or_default();
+                }
+                let position = backend.player.position_secs();
+                let Some(segment) = segments
+                    .iter()
+                    // Half a second of slack keeps a seek landing short of the end from looping
+                    .find(|s| position >= s.segment[0] && position < s.segment[1] - 0.5)
+                else {
+                    continue;
+                };
+                let [start, end] = segment.segment;
+                if backend.player.seek_to(end).is_ok() {
+                    let kind = if segment.category == "sponsor" { "sponsor" } else { "non-music" };
+                    status.set_message(
+                        format!("Skipped {} {}", format_time(end - start), kind),
+                        MESSAGE_DURATION,
+                    );
+                    redraw.request();
+                }
+            }
+        }
+    }
+}
+
+// Looks up the segments of a video. SponsorBlock answers 404 when there are none.
+async fn fetch_segments(http: &reqwest::Client, video_id: &str) -> Result<Vec<Segment>, String> {
+    let response = http
+        .get(API_URL)
+        .query(&[("videoID", video_id), ("categories", CATEGORIES)])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let segments: Vec<Segment> = response
+        .error_for_status()
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(segments
+        .into_iter()
+        .filter(|s| s.segment[1] - s.segment[0] >= MIN_SKIP)
+        .collect())
+}
+
+fn format_time(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
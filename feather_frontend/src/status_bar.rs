@@ -0,0 +1,86 @@
+use crate::theme::{accent_style, error_style, success_style, text_style};
+use ratatui::prelude::{Buffer, Constraint, Layout, Rect};
+use ratatui::widgets::{Paragraph, Widget};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const MESSAGE_DURATION: Duration = Duration::from_secs(3); // How long a message usually stays up
+pub const ERROR_DURATION: Duration = Duration::from_secs(6); // Errors stay longer so they can be read
+
+#[derive(Clone, Copy, PartialEq)]
+enum Level {
+    Info,  // Confirmation of a finished action
+    Error, // Something the user asked for failed
+}
+
+struct StatusMessage {
+    text: String,   // Text shown in place of the hints
+    until: Instant, // When the message disappears
+    level: Level,   // Decides the message color
+}
+
+// Single line at the bottom of the screen showing keystroke hints or a short-lived message.
+// Clones share the same message, so every component can hold its own handle.
+#[derive(Clone, Default)]
+pub struct StatusBar {
+    message: Arc<Mutex<Option<StatusMessage>>>,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Shows a message over the hints for the given duration
+    pub fn set_message(&self, text: impl Into<String>, duration: Duration) {
+        self.set(text.into(), duration, Level::Info);
+    }
+
+    // Like `set_message`, but rendered as an error
+    pub fn set_error(&self, text: impl Into<String>, duration: Duration) {
+        self.set(text.into(), duration, Level::Error);
+    }
+
+    // Shows an info message or an error depending on the result of an action
+    pub fn report(&self, result: Result<(), String>, success: impl Into<String>) {
+        match result {
+            Ok(()) => self.set_message(success, MESSAGE_DURATION),
+            Err(e) => self.set_error(e, MESSAGE_DURATION),
+        }
+    }
+
+    fn set(&self, text: String, duration: Duration, level: Level) {
+        if let Ok(mut message) = self.message.lock() {
+            *message = Some(StatusMessage {
+                text,
+                until: Instant::now() + duration,
+                level,
+            });
+        }
+    }
+
+    // Renders the active message, falling back to the hints once it has expired.
+    // `pending` holds keys typed so far, such as a count, and sits at the right edge.
+    pub fn render(&self, area: Rect, buf: &mut Buffer, hints: &str, pending: &str) {
+        let [area, pending_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(pending.len() as u16)])
+                .areas(area);
+        Paragraph::new(pending)
+            .style(accent_style())
+            .render(pending_area, buf);
+
+        let mut message = match self.message.lock() {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+        if message.as_ref().is_some_and(|m| Instant::now() >= m.until) {
+            *message = None; // Expired
+        }
+        let (text, style) = match message.as_ref() {
+            Some(m) if m.level == Level::Error => (m.text.as_str(), error_style()),
+            Some(m) => (m.text.as_str(), success_style()),
+            None => (hints, text_style()),
+        };
+        Paragraph::new(text).style(style).render(area, buf);
+    }
+}
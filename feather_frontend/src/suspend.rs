@@ -0,0 +1,57 @@
+use crate::debug_log;
+use crate::redraw::Redraw;
+use crate::status_bar::{MESSAGE_DURATION, StatusBar};
+use feather::backend::Backend;
+use std::env;
+use std::sync::Arc;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::task::JoinHandle;
+
+// Pauses playback when Feather receives SIGUSR2, enabled with FEATHER_PAUSE_ON_SUSPEND=1,
+// so other programs can pause it, e.g. `pkill -USR2 feather` from a call or video hook.
+// Resuming stays manual.
+pub struct PauseOnSuspend {
+    task: JoinHandle<()>, // Waits for the signal until the app exits
+}
+
+impl PauseOnSuspend {
+    pub fn start(backend: Arc<Backend>, status: StatusBar, redraw: Redraw) -> Option<Self> {
+        if env::var("FEATHER_PAUSE_ON_SUSPEND").as_deref() != Ok("1") {
+            return None;
+        }
+        // Registered here rather than in the task, so a signal sent right after startup
+        // is not lost and a failure is logged before anything plays
+        let mut signals = match signal(SignalKind::user_defined2()) {
+            Ok(signals) => signals,
+            Err(e) => {
+                debug_log::log(&format!(
+                    "pause on suspend: cannot listen for SIGUSR2: {}",
+                    e
+                ));
+                return None;
+            }
+        };
+        Some(Self {
+            task: tokio::spawn(async move {
+                while signals.recv().await.is_some() {
+                    if !backend.player.is_playing().unwrap_or(false) {
+                        continue;
+                    }
+                    match backend.player.pause() {
+                        Ok(()) => {
+                            status.set_message("Paused by another application", MESSAGE_DURATION)
+                        }
+                        Err(e) => debug_log::log(&format!("pause on suspend: {}", e)),
+                    }
+                    redraw.request();
+                }
+            }),
+        })
+    }
+}
+
+impl Drop for PauseOnSuspend {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,53 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Cuts text to fit the given number of terminal columns, ending it with "…" when shortened.
+// Works on display width, so wide CJK characters count twice and combining marks not at all.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let budget = width - 1; // Room for the ellipsis
+    let mut used = 0;
+    let mut truncated = String::new();
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > budget {
+            break; // Combining marks of a dropped character go with it
+        }
+        used += char_width;
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_by_display_width() {
+        let cases = [
+            ("hello", 5, "hello"),
+            ("hello world", 5, "hell…"),
+            ("hello", 0, ""),
+            // Wide characters take two columns
+            ("日本語の歌", 10, "日本語の歌"),
+            ("日本語の歌", 6, "日本…"),
+            ("日本語の歌", 5, "日本…"),
+            ("a日本", 4, "a日…"), // A wide character never straddles the cut
+            // Combining marks take none and stay with their letter
+            ("Cafe\u{301} Song", 9, "Cafe\u{301} Song"),
+            ("Cafe\u{301} Song", 5, "Cafe\u{301}…"),
+            ("abe\u{301}c", 3, "ab…"), // ... or go with it
+        ];
+        for (text, width, expected) in cases {
+            let truncated = truncate_to_width(text, width);
+            assert_eq!(truncated, expected, "{:?} cut to {} columns", text, width);
+            assert!(truncated.width() <= width);
+        }
+    }
+}
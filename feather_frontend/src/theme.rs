@@ -0,0 +1,116 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::env;
+use std::sync::OnceLock;
+
+pub const NOW_PLAYING: &str = "♪ "; // Prefix of the row that is currently playing
+
+// Shared styles, so every widget draws selections, accents and messages the same way
+
+// Row under the cursor in any list, its text colored by FEATHER_SELECTED_COLOR if set
+pub fn selected_style() -> Style {
+    static COLOR: OnceLock<Color> = OnceLock::new();
+    let color = COLOR.get_or_init(|| {
+        env::var("FEATHER_SELECTED_COLOR")
+            .ok()
+            .and_then(|value| parse_rgb(&value))
+            .unwrap_or(Color::Yellow)
+    });
+    Style::default().fg(*color).bg(Color::Blue)
+}
+
+// Reads a color written as "#rrggbb" or "r,g,b"
+fn parse_rgb(value: &str) -> Option<Color> {
+    let value = value.trim();
+    let [r, g, b] = if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        [channel(0)?, channel(2)?, channel(4)?]
+    } else {
+        let channels: Vec<u8> = value
+            .split(',')
+            .map(|channel| channel.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        channels.try_into().ok()?
+    };
+    Some(Color::Rgb(r, g, b))
+}
+
+// Regular text
+pub fn text_style() -> Style {
+    Style::default().fg(Color::White)
+}
+
+// Highlights such as the active tab, section headings and the progress bar
+pub fn accent_style() -> Style {
+    Style::default().fg(Color::Yellow)
+}
+
+// Secondary elements such as the unplayed part of the progress bar
+pub fn muted_style() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+// Confirmations of finished actions
+pub fn success_style() -> Style {
+    Style::default().fg(Color::Green)
+}
+
+// Failures shown to the user
+pub fn error_style() -> Style {
+    Style::default().fg(Color::Red)
+}
+
+// Border style for a pane, highlighted when it receives the keys
+pub fn border_style(focused: bool) -> Style {
+    if focused {
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_map_to_the_exact_channels() {
+        let cases = [
+            ("#102030", Some(Color::Rgb(0x10, 0x20, 0x30))),
+            ("#A0b0C0", Some(Color::Rgb(0xa0, 0xb0, 0xc0))),
+            (" #ffffff ", Some(Color::Rgb(255, 255, 255))),
+            ("16,32,48", Some(Color::Rgb(16, 32, 48))),
+            ("1, 2, 3", Some(Color::Rgb(1, 2, 3))),
+            ("0,0,255", Some(Color::Rgb(0, 0, 255))), // Blue stays in the blue channel
+            ("#12345", None),
+            ("#1234567", None),
+            ("#gg0000", None),
+            ("#ééé", None),
+            ("256,0,0", None),
+            ("1,2", None),
+            ("1,2,3,4", None),
+            ("yellow", None),
+            ("", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_rgb(value), expected, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn styles_use_their_colors() {
+        assert_eq!(selected_style().bg, Some(Color::Blue));
+        assert_eq!(text_style().fg, Some(Color::White));
+        assert_eq!(accent_style().fg, Some(Color::Yellow));
+        assert_eq!(muted_style().fg, Some(Color::DarkGray));
+        assert_eq!(success_style().fg, Some(Color::Green));
+        assert_eq!(error_style().fg, Some(Color::Red));
+        assert_eq!(border_style(false), Style::default());
+        assert_eq!(border_style(true).fg, Some(Color::Cyan));
+        assert!(border_style(true).add_modifier.contains(Modifier::BOLD));
+    }
+}